@@ -0,0 +1,222 @@
+use std::{collections::HashMap, fmt::Display};
+
+use serde::{Deserialize, Serialize};
+
+use crate::RefrigerantName;
+
+/// What the per-component fractions of a reading or mixture are proportions of.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CompositionBasis {
+    /// Mass fractions, the basis ASHRAE 34 blend compositions are given in.
+    #[default]
+    Mass,
+    /// Mole fractions, what a TCD quantified with molar response factors reports.
+    Mole,
+    /// Normalized raw peak areas, only convertible once response factors are known.
+    AreaPercent,
+}
+
+impl Display for CompositionBasis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                CompositionBasis::Mass => "mass",
+                CompositionBasis::Mole => "mole",
+                CompositionBasis::AreaPercent => "area_percent",
+            }
+        )
+    }
+}
+
+pub(crate) fn mole_basis() -> CompositionBasis {
+    CompositionBasis::Mole
+}
+
+fn molar_mass(name: &RefrigerantName) -> Result<f64, String> {
+    name.properties()
+        .map(|p| p.molar_mass)
+        .ok_or(format!("No molar mass known for {}.", name))
+}
+
+/// Rescales `weighted` so that it sums to the same total as `original`, keeping unexplained
+/// remainders (unidentified peaks, incomplete readings) the same size across conversions.
+fn rescale(
+    original: &HashMap<RefrigerantName, f64>,
+    weighted: HashMap<RefrigerantName, f64>,
+) -> HashMap<RefrigerantName, f64> {
+    let scale = scale(original, weighted.values().sum::<f64>());
+
+    weighted
+        .into_iter()
+        .map(|(name, v)| (name, v * scale))
+        .collect()
+}
+
+fn scale(original: &HashMap<RefrigerantName, f64>, weighted_total: f64) -> f64 {
+    if weighted_total == 0. {
+        1.
+    } else {
+        original.values().sum::<f64>() / weighted_total
+    }
+}
+
+/// Relative factor a component is multiplied with when converting from one basis to another,
+/// before rescaling.
+fn weight(
+    name: &RefrigerantName,
+    from: CompositionBasis,
+    to: CompositionBasis,
+) -> Result<f64, String> {
+    use CompositionBasis::*;
+
+    match (from, to) {
+        (Mass, Mass) | (Mole, Mole) | (AreaPercent, AreaPercent) => Ok(1.),
+        (Mass, Mole) => Ok(1. / molar_mass(name)?),
+        (Mole, Mass) => molar_mass(name),
+        (AreaPercent, _) => {
+            Err("Area percent readings need response factors to be converted.".into())
+        }
+        (_, AreaPercent) => Err("Cannot convert a composition to area percent.".into()),
+    }
+}
+
+/// Converts component fractions between the mass and mole basis using the molar masses from
+/// the property database.
+pub fn convert(
+    components: &HashMap<RefrigerantName, f64>,
+    from: CompositionBasis,
+    to: CompositionBasis,
+) -> Result<HashMap<RefrigerantName, f64>, String> {
+    if from == to {
+        return Ok(components.clone());
+    }
+
+    let weighted = components
+        .iter()
+        .map(|(name, &v)| Ok((name.clone(), v * weight(name, from, to)?)))
+        .collect::<Result<HashMap<_, _>, String>>()?;
+
+    Ok(rescale(components, weighted))
+}
+
+/// Factors [`convert`] would multiply `names` with inside `components`, for values that are not
+/// part of the composition themselves, like detection limits.
+pub(crate) fn conversion_factors<'a>(
+    components: &HashMap<RefrigerantName, f64>,
+    names: impl IntoIterator<Item = &'a RefrigerantName>,
+    from: CompositionBasis,
+    to: CompositionBasis,
+) -> Result<HashMap<RefrigerantName, f64>, String> {
+    let weighted_total = components
+        .iter()
+        .map(|(name, &v)| Ok(v * weight(name, from, to)?))
+        .sum::<Result<f64, String>>()?;
+
+    let scale = scale(components, weighted_total);
+
+    names
+        .into_iter()
+        .map(|name| Ok((name.clone(), weight(name, from, to)? * scale)))
+        .collect()
+}
+
+/// Turns area percentages into mole fractions using relative molar response factors
+/// (mole fraction per unit area). Components without a response factor are an error.
+pub fn calibrate(
+    areas: &HashMap<RefrigerantName, f64>,
+    response_factors: &HashMap<RefrigerantName, f64>,
+) -> Result<HashMap<RefrigerantName, f64>, String> {
+    let weighted = areas
+        .iter()
+        .map(|(name, &a)| {
+            response_factors
+                .get(name)
+                .map(|rf| (name.clone(), a * rf))
+                .ok_or(format!("No response factor for {}.", name))
+        })
+        .collect::<Result<HashMap<_, _>, String>>()?;
+
+    Ok(rescale(areas, weighted))
+}
+
+/// Factors [`calibrate`] would multiply `names` with inside `areas`, see
+/// [`conversion_factors`].
+pub(crate) fn calibration_factors<'a>(
+    areas: &HashMap<RefrigerantName, f64>,
+    response_factors: &HashMap<RefrigerantName, f64>,
+    names: impl IntoIterator<Item = &'a RefrigerantName>,
+) -> Result<HashMap<RefrigerantName, f64>, String> {
+    let response_factor = |name: &RefrigerantName| {
+        response_factors
+            .get(name)
+            .copied()
+            .ok_or(format!("No response factor for {}.", name))
+    };
+
+    let weighted_total = areas
+        .iter()
+        .map(|(name, &a)| Ok(a * response_factor(name)?))
+        .sum::<Result<f64, String>>()?;
+
+    let scale = scale(areas, weighted_total);
+
+    names
+        .into_iter()
+        .map(|name| Ok((name.clone(), response_factor(name)? * scale)))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use nearly::assert_nearly;
+
+    use super::*;
+
+    fn r410a() -> HashMap<RefrigerantName, f64> {
+        HashMap::from([
+            ("R-32".parse().unwrap(), 0.5),
+            ("R-125".parse().unwrap(), 0.5),
+        ])
+    }
+
+    #[test]
+    fn mass_to_mole() {
+        let r32 = "R-32".parse().unwrap();
+        let mole = convert(&r410a(), CompositionBasis::Mass, CompositionBasis::Mole).unwrap();
+
+        // R-410A is ~69.8 mol% R-32
+        assert_nearly!(mole[&r32] == 0.69763, eps = 1e-4);
+        assert_nearly!(mole.values().sum::<f64>() == 1.);
+    }
+
+    #[test]
+    fn round_trip_keeps_total() {
+        let mut partial = r410a();
+        partial.values_mut().for_each(|v| *v *= 0.9);
+
+        let mole = convert(&partial, CompositionBasis::Mass, CompositionBasis::Mole).unwrap();
+        let mass = convert(&mole, CompositionBasis::Mole, CompositionBasis::Mass).unwrap();
+
+        partial
+            .iter()
+            .for_each(|(name, v)| assert_nearly!(mass[name] == *v));
+    }
+
+    #[test]
+    fn area_percent_needs_response_factors() {
+        assert!(
+            convert(
+                &r410a(),
+                CompositionBasis::AreaPercent,
+                CompositionBasis::Mole
+            )
+            .is_err()
+        );
+
+        let factors = HashMap::from([("R-32".parse().unwrap(), 1.0)]);
+        assert!(calibrate(&r410a(), &factors).is_err());
+    }
+}
@@ -0,0 +1,100 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+/// Limits of detection and quantitation of one component, in the units of the reading.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(try_from = "LimitsDef")]
+pub struct DetectionLimits {
+    /// Smallest amount distinguishable from baseline noise.
+    pub lod: f64,
+    /// Smallest amount that can be reported as a number.
+    pub loq: f64,
+}
+
+#[derive(Deserialize)]
+struct LimitsDef {
+    lod: f64,
+    loq: f64,
+}
+
+/// How a component of a reading was observed.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Detection {
+    /// Not detected, the true value is somewhere below the limit of detection.
+    NotDetected,
+    /// Detected, but below the limit of quantitation so the value is only indicative.
+    BelowQuantitation,
+    Quantified,
+}
+
+impl DetectionLimits {
+    pub fn new(lod: f64, loq: f64) -> Result<Self, String> {
+        if !(0. ..=loq).contains(&lod) {
+            return Err(format!(
+                "Need 0 <= limit of detection ({}) <= limit of quantitation ({}).",
+                lod, loq
+            ));
+        }
+
+        Ok(Self { lod, loq })
+    }
+
+    /// Classifies a reported value, `None` meaning no peak was found.
+    pub fn classify(&self, value: Option<f64>) -> Detection {
+        match value.filter(|&v| v > 0. && v >= self.lod) {
+            Some(v) if v >= self.loq => Detection::Quantified,
+            Some(_) => Detection::BelowQuantitation,
+            None => Detection::NotDetected,
+        }
+    }
+
+    /// Both limits multiplied by `factor`, for converting them along with their reading.
+    pub fn scaled(&self, factor: f64) -> Self {
+        Self {
+            lod: self.lod * factor,
+            loq: self.loq * factor,
+        }
+    }
+}
+
+impl TryFrom<LimitsDef> for DetectionLimits {
+    type Error = String;
+
+    fn try_from(value: LimitsDef) -> Result<Self, Self::Error> {
+        Self::new(value.lod, value.loq)
+    }
+}
+
+impl Display for Detection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Detection::NotDetected => "ND",
+                Detection::BelowQuantitation => "<LOQ",
+                Detection::Quantified => "quantified",
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn classify_against_limits() {
+        let limits = DetectionLimits::new(0.0001, 0.0005).unwrap();
+
+        assert_eq!(limits.classify(None), Detection::NotDetected);
+        assert_eq!(limits.classify(Some(0.00005)), Detection::NotDetected);
+        assert_eq!(limits.classify(Some(0.0002)), Detection::BelowQuantitation);
+        assert_eq!(limits.classify(Some(0.0005)), Detection::Quantified);
+
+        assert!(DetectionLimits::new(0.001, 0.0001).is_err());
+        assert!(serde_json::from_str::<DetectionLimits>(r#"{ "lod": -1, "loq": 0 }"#).is_err());
+    }
+}
@@ -2,38 +2,63 @@ use std::{
     collections::{HashMap, HashSet},
     fmt::Display,
     hash::Hash,
-    ptr::addr_of,
+    str::FromStr,
 };
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-const DEFAULT_LABEL: &str = "Mixed";
+pub mod composition;
+pub mod detection;
+mod name;
+pub mod properties;
+pub mod registry;
+pub mod spec;
+pub mod tolerance;
 
-const DEFAULT_PURITY: f64 = 0.995;
+pub use composition::CompositionBasis;
+pub use detection::{Detection, DetectionLimits};
+pub use name::{AliasTable, AliasTarget};
+use properties::{Assessment, RefrigerantProperties};
+pub use registry::{MixtureId, MixtureRegistry};
+use tolerance::{ComponentDeviation, CompositionCheck, Tolerance};
 
-#[derive(Deserialize, PartialEq, Eq, Debug, Clone, PartialOrd, Ord)]
+/// Label of readings that no classification of their origin accepts.
+pub const DEFAULT_LABEL: &str = "Mixed";
+
+/// Purity at which a mixture without classifications is labelled with its own name.
+pub const DEFAULT_PURITY: f64 = 0.995;
+
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone, PartialOrd, Ord)]
 #[serde(try_from = "String")]
 pub struct RefrigerantName(String);
 
-#[derive(Deserialize, PartialEq, Debug, Default)]
+#[derive(Deserialize, PartialEq, Debug, Default, Clone)]
 #[serde(try_from = "HashMap<String, RefrigerantClassification>")]
-pub struct ClassificationList<'a>(Vec<(String, RefrigerantClassification<'a>)>);
+pub struct ClassificationList(Vec<(String, RefrigerantClassification)>);
 
-#[derive(Deserialize, PartialEq, Debug)]
-
-pub struct RefrigerantMixture<'a> {
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+pub struct RefrigerantMixture {
     identifier: RefrigerantName,
     components: HashMap<RefrigerantName, f64>,
     #[serde(default)]
-    classifications: ClassificationList<'a>,
+    basis: CompositionBasis,
+    #[serde(default)]
+    tolerances: HashMap<RefrigerantName, Tolerance>,
+    #[serde(default)]
+    classifications: ClassificationList,
 }
 
-#[derive(Deserialize, PartialEq, Debug)]
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
 pub struct GCReading {
     components: HashMap<RefrigerantName, f64>,
+    #[serde(default = "composition::mole_basis")]
+    basis: CompositionBasis,
+    /// Detection and quantitation limits, in the same basis as the components.
+    #[serde(default)]
+    limits: HashMap<RefrigerantName, DetectionLimits>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Serialize, Debug, Clone)]
 pub struct ClassificationResult {
     pub label: String,
     pub origin: RefrigerantName,
@@ -41,133 +66,209 @@ pub struct ClassificationResult {
     pub components: HashMap<RefrigerantName, f64>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
-#[serde(from = "RefrigerantName")]
-enum RefrigerantRef<'a> {
-    Unresolved(RefrigerantName),
-    Resolved(&'a RefrigerantMixture<'a>),
-}
-
-#[derive(Debug, Deserialize)]
-pub struct RefrigerantClassification<'a> {
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct RefrigerantClassification {
     purity: f64,
     max_lows: Option<f64>,
+    /// Mixtures allowed alongside the origin, by identifier, with their maximum concentration.
     #[serde(default)]
-    mixed_with: HashMap<RefrigerantRef<'a>, f64>,
+    mixed_with: HashMap<RefrigerantName, f64>,
 }
 
-impl<'a> Eq for RefrigerantClassification<'a> {}
+impl RefrigerantClassification {
+    pub fn new(purity: f64, max_lows: Option<f64>) -> Self {
+        Self {
+            purity,
+            max_lows,
+            mixed_with: HashMap::new(),
+        }
+    }
 
-impl<'a> PartialEq for RefrigerantClassification<'a> {
-    fn eq(&self, other: &Self) -> bool {
-        addr_of!(self) == addr_of!(other)
+    pub fn with_mixed(mut self, mixture: RefrigerantName, max: f64) -> Self {
+        self.mixed_with.insert(mixture, max);
+        self
     }
-}
 
-impl<'a> PartialEq for RefrigerantRef<'a> {
-    fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            (Self::Unresolved(l0), Self::Unresolved(r0)) => l0 == r0,
-            (Self::Resolved(l0), Self::Resolved(r0)) => l0 == r0,
-            _ => false,
-        }
+    /// Whether a reading where the origin makes up `purity`, the largest low level contaminant
+    /// is `max_low` and other mixtures make up `mixtures` falls under this classification.
+    /// Mixtures listed in `mixed_with` count towards the purity up to their maximum.
+    pub fn accepts(
+        &self,
+        purity: f64,
+        max_low: f64,
+        mixtures: &HashMap<RefrigerantName, f64>,
+    ) -> bool {
+        let allowed = self
+            .mixed_with
+            .iter()
+            .map(|(name, max)| mixtures.get(name).map_or(0., |c| c.min(*max)))
+            .sum::<f64>();
+
+        purity + allowed >= self.purity && self.max_lows.is_none_or(|l| max_low <= l)
     }
 }
 
-impl<'a> Hash for RefrigerantRef<'a> {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        core::mem::discriminant(self).hash(state);
+impl Display for RefrigerantName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
     }
 }
 
-impl<'a> Eq for RefrigerantRef<'a> {}
+impl TryFrom<HashMap<String, RefrigerantClassification>> for ClassificationList {
+    type Error = String;
 
-impl<'a> From<RefrigerantName> for RefrigerantRef<'a> {
-    fn from(value: RefrigerantName) -> Self {
-        Self::Unresolved(value)
+    fn try_from(value: HashMap<String, RefrigerantClassification>) -> Result<Self, Self::Error> {
+        Ok(Self(value.into_iter().collect()))
     }
 }
 
-impl<'a> RefrigerantClassification<'a> {
-    fn evaluate(&self, reading: &GCReading, origin: &RefrigerantMixture)
-    /* -> math::OptimizationResult<'a> */
-    {
-        // if math::valid_comparison(reading, origin) {
-        //     return Err("Not a valid comparison".into());
-        // }
+impl Serialize for ClassificationList {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().map(|(label, class)| (label, class)))
+    }
+}
 
-        // let max_low = math::find_max_low(reading, origin);
+impl ClassificationList {
+    /// Classifications from the strictest to the most lenient purity.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &RefrigerantClassification)> {
+        let mut list = self
+            .0
+            .iter()
+            .map(|(label, class)| (label.as_str(), class))
+            .collect::<Vec<_>>();
+        list.sort_by(|(_, a), (_, b)| b.purity.total_cmp(&a.purity));
+
+        list.into_iter()
+    }
 
-        // if self.max_lows.is_some_and(|l| max_low > l) {
-        //     return Err("Max lows not within bounds".into());
-        // }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
 
-        // todo!()
+    /// Label of the strictest classification accepting the reading, see
+    /// [`RefrigerantClassification::accepts`]. Without classifications the origin's own name is
+    /// used at [`DEFAULT_PURITY`], [`DEFAULT_LABEL`] if nothing accepts.
+    pub fn get_classification(
+        &self,
+        origin: &RefrigerantName,
+        purity: f64,
+        max_low: f64,
+        mixtures: &HashMap<RefrigerantName, f64>,
+    ) -> String {
+        let label = if self.is_empty() {
+            (purity >= DEFAULT_PURITY).then(|| origin.to_string())
+        } else {
+            self.iter()
+                .find(|(_, class)| class.accepts(purity, max_low, mixtures))
+                .map(|(label, _)| label.to_string())
+        };
+
+        label.unwrap_or(DEFAULT_LABEL.into())
     }
 }
 
-impl Display for RefrigerantName {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+impl GCReading {
+    pub fn new(components: HashMap<RefrigerantName, f64>, basis: CompositionBasis) -> Self {
+        Self {
+            components,
+            basis,
+            limits: HashMap::new(),
+        }
     }
-}
 
-impl<'a> TryFrom<HashMap<String, RefrigerantClassification<'a>>> for ClassificationList<'a> {
-    type Error = String;
+    pub fn with_limits(mut self, limits: HashMap<RefrigerantName, DetectionLimits>) -> Self {
+        self.limits = limits;
+        self
+    }
 
-    fn try_from(
-        value: HashMap<String, RefrigerantClassification<'a>>,
-    ) -> Result<Self, Self::Error> {
-        Ok(Self(value.into_iter().collect()))
+    pub fn basis(&self) -> CompositionBasis {
+        self.basis
     }
-}
 
-impl<'a> ClassificationList<'a> {
-    pub fn get_classification(
+    /// Converts the reading to another basis, see [`composition::convert`]. Detection limits
+    /// are converted with the same factors as their components.
+    pub fn to_basis(&self, basis: CompositionBasis) -> Result<Self, String> {
+        let factors = composition::conversion_factors(
+            &self.components,
+            self.limits.keys(),
+            self.basis,
+            basis,
+        )?;
+
+        Ok(Self::new(
+            composition::convert(&self.components, self.basis, basis)?,
+            basis,
+        )
+        .with_limits(self.scaled_limits(&factors)))
+    }
+
+    /// Turns an area percent reading into mole fractions, see [`composition::calibrate`].
+    pub fn calibrate(
         &self,
-        reading: &GCReading,
-        origin: &RefrigerantMixture<'a>,
-    ) -> ClassificationResult {
-        // match self.0.iter().find_map(|(name, class)| {
-        //     class
-        //         .evaluate(reading, origin)
-        //         .map(|(results, fin)| (results, fin, name))
-        //         .ok()
-        // }) {
-        //     Some((mixtures, _, name)) => ClassificationResult {
-        //         label: name.clone(),
-        //         origin: origin.identifier().clone(),
-        //         purity: mixtures
-        //             .iter()
-        //             .find(|m| m.1.identifier() == origin.identifier())
-        //             .unwrap()
-        //             .0,
-        //         components: mixtures
-        //             .iter()
-        //             .map(|o| (o.1.identifier().clone(), o.0))
-        //             .collect(),
-        //     },
-        //     None => ClassificationResult {
-        //         label: DEFAULT_LABEL.into(),
-        //         origin: origin.identifier().clone(),
-        //         purity: 0.,
-        //         components: HashMap::new(),
-        //     },
-        // }
-
-        todo!()
+        response_factors: &HashMap<RefrigerantName, f64>,
+    ) -> Result<Self, String> {
+        if self.basis != CompositionBasis::AreaPercent {
+            return Err("Only area percent readings can be calibrated.".into());
+        }
+
+        let factors = composition::calibration_factors(
+            &self.components,
+            response_factors,
+            self.limits.keys(),
+        )?;
+
+        Ok(Self::new(
+            composition::calibrate(&self.components, response_factors)?,
+            CompositionBasis::Mole,
+        )
+        .with_limits(self.scaled_limits(&factors)))
     }
-}
 
-impl GCReading {
-    pub fn new(components: HashMap<RefrigerantName, f64>) -> Self {
-        Self { components }
+    fn scaled_limits(
+        &self,
+        factors: &HashMap<RefrigerantName, f64>,
+    ) -> HashMap<RefrigerantName, DetectionLimits> {
+        self.limits
+            .iter()
+            .map(|(name, limits)| (name.clone(), limits.scaled(factors[name])))
+            .collect()
     }
 
     pub fn get_component(&self, name: &RefrigerantName) -> Option<&f64> {
         self.components.get(name)
     }
 
+    pub fn limits(&self, name: &RefrigerantName) -> Option<&DetectionLimits> {
+        self.limits.get(name)
+    }
+
+    /// Components with detection limits, whether or not they were detected.
+    pub fn limited_components(&self) -> impl Iterator<Item = (&RefrigerantName, &DetectionLimits)> {
+        self.limits.iter()
+    }
+
+    /// Without limits a component counts as quantified whenever it has a positive value.
+    pub fn detection(&self, name: &RefrigerantName) -> Detection {
+        let value = self.get_component(name).copied();
+
+        match self.limits(name) {
+            Some(limits) => limits.classify(value),
+            None if value.is_some_and(|v| v > 0.) => Detection::Quantified,
+            None => Detection::NotDetected,
+        }
+    }
+
+    /// Largest amount of the component consistent with the reading: the limit of detection
+    /// for components that were not detected, the reported value otherwise.
+    pub fn upper_bound(&self, name: &RefrigerantName) -> f64 {
+        let value = self.get_component(name).copied().unwrap_or(0.);
+
+        match self.detection(name) {
+            Detection::NotDetected => value.max(self.limits(name).map_or(0., |l| l.lod)),
+            _ => value,
+        }
+    }
+
     pub fn component_set(&self) -> HashSet<&RefrigerantName> {
         self.components.keys().collect()
     }
@@ -181,16 +282,23 @@ impl TryFrom<String> for GCReading {
     type Error = String;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        let data = value.split(",").map(|s| {
-            let parts: Vec<&str> = s.trim().split(" ").collect();
-            let name = RefrigerantName::new(&parts[0].into()).unwrap();
-            let concentration = parts[1].to_string().trim().parse::<f64>().unwrap();
-            (name, concentration)
-        });
-
-        let data: HashMap<RefrigerantName, f64> = HashMap::from_iter(data);
-
-        Ok(GCReading::new(data))
+        let data = value
+            .split(",")
+            .map(|s| {
+                let (name, concentration) = s
+                    .trim()
+                    .split_once(" ")
+                    .ok_or_else(|| format!("\"{}\" is missing a concentration.", s.trim()))?;
+                let name = name.parse::<RefrigerantName>()?;
+                let concentration = concentration
+                    .trim()
+                    .parse::<f64>()
+                    .map_err(|e| format!("Invalid concentration for {name}: {e}"))?;
+                Ok((name, concentration))
+            })
+            .collect::<Result<HashMap<RefrigerantName, f64>, String>>()?;
+
+        Ok(GCReading::new(data, CompositionBasis::Mole))
     }
 }
 
@@ -207,21 +315,63 @@ impl Display for ClassificationResult {
     }
 }
 
-impl<'a> RefrigerantMixture<'a> {
+impl RefrigerantMixture {
     pub fn new(
         identifier: RefrigerantName,
         components: HashMap<RefrigerantName, f64>,
-        classifications: ClassificationList<'a>,
+        classifications: ClassificationList,
     ) -> Self {
         Self {
             identifier,
             components,
+            basis: CompositionBasis::Mass,
+            tolerances: HashMap::new(),
             classifications,
         }
     }
 
-    pub fn classify(&self, reading: &GCReading) -> ClassificationResult {
-        self.classifications.get_classification(reading, self)
+    pub fn basis(&self) -> CompositionBasis {
+        self.basis
+    }
+
+    /// Component fractions expressed in `basis`, see [`composition::convert`].
+    pub fn fractions(
+        &self,
+        basis: CompositionBasis,
+    ) -> Result<HashMap<RefrigerantName, f64>, String> {
+        composition::convert(&self.components, self.basis, basis)
+    }
+
+    /// Classifies a reading this mixture makes up `purity` of, given the concentrations of
+    /// every mixture it was fitted with (including this one) and its largest low level
+    /// contaminant, see [`ClassificationList::get_classification`].
+    pub fn classify(
+        &self,
+        purity: f64,
+        max_low: f64,
+        components: HashMap<RefrigerantName, f64>,
+    ) -> ClassificationResult {
+        let mixtures = components
+            .iter()
+            .filter(|(name, _)| **name != self.identifier)
+            .map(|(name, c)| (name.clone(), *c))
+            .collect();
+
+        ClassificationResult {
+            label: self.classifications.get_classification(
+                &self.identifier,
+                purity,
+                max_low,
+                &mixtures,
+            ),
+            origin: self.identifier.clone(),
+            purity,
+            components,
+        }
+    }
+
+    pub fn classifications(&self) -> &ClassificationList {
+        &self.classifications
     }
 
     pub fn get_component(&self, name: &RefrigerantName) -> Option<&f64> {
@@ -236,9 +386,81 @@ impl<'a> RefrigerantMixture<'a> {
         &self.identifier
     }
 
+    pub fn tolerance(&self, name: &RefrigerantName) -> Option<&Tolerance> {
+        self.tolerances.get(name)
+    }
+
+    /// Compares the blend components of `reading` against the nominal composition and its
+    /// tolerances. The reading is renormalized over the blend components, so contaminants do
+    /// not shift the comparison and have to be judged separately.
+    pub fn check_composition(&self, reading: &GCReading) -> Result<CompositionCheck, String> {
+        // contaminants may lack the molar mass a conversion needs
+        let blend = reading
+            .components()
+            .filter(|(name, _)| self.components.contains_key(*name))
+            .map(|(name, v)| (name.clone(), *v))
+            .collect();
+        let reading = GCReading::new(blend, reading.basis()).to_basis(self.basis)?;
+        let measured = |name| reading.get_component(name).map_or(0., |v| *v);
+
+        let blend_total = self
+            .components()
+            .map(|(name, _)| measured(name))
+            .sum::<f64>();
+
+        if blend_total <= 0. {
+            return Err(format!(
+                "Reading contains none of the components of {}.",
+                self.identifier
+            ));
+        }
+
+        let mut components = self
+            .components()
+            .map(|(name, &nominal)| {
+                let tolerance = self.tolerance(name).ok_or(format!(
+                    "No tolerance given for {} in {}.",
+                    name, self.identifier
+                ))?;
+
+                Ok(ComponentDeviation {
+                    name: name.clone(),
+                    nominal,
+                    measured: measured(name) / blend_total,
+                    tolerance: *tolerance,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        components.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(CompositionCheck {
+            mixture: self.identifier.clone(),
+            components,
+        })
+    }
+
     pub fn component_set(&self) -> HashSet<&RefrigerantName> {
         self.components.keys().collect()
     }
+
+    /// Mass-weighted GWP of the mixture, `None` if a component is missing from the database.
+    pub fn gwp(&self, assessment: Assessment) -> Option<f64> {
+        self.fractions(CompositionBasis::Mass)
+            .ok()?
+            .iter()
+            .map(|(name, fraction)| Some(name.properties()?.gwp.get(assessment) * fraction))
+            .sum()
+    }
+
+    /// Mass-weighted ODP of the mixture, `None` if a component is missing from the database.
+    pub fn odp(&self) -> Option<f64> {
+        self.fractions(CompositionBasis::Mass)
+            .ok()?
+            .iter()
+            .map(|(name, fraction)| Some(name.properties()?.odp * fraction))
+            .sum()
+    }
 }
 
 impl Hash for RefrigerantName {
@@ -248,12 +470,24 @@ impl Hash for RefrigerantName {
 }
 
 impl RefrigerantName {
-    pub fn new(name: &String) -> Option<Self> {
+    pub fn new(name: &str) -> Option<Self> {
         Self::normalize(name).map(RefrigerantName)
     }
 
-    fn normalize(name: &String) -> Option<String> {
-        Some(name.to_uppercase().replace(" ", ""))
+    fn normalize(name: &str) -> Option<String> {
+        name::canonicalize(name).ok()
+    }
+
+    pub fn properties(&self) -> Option<&'static RefrigerantProperties> {
+        properties::lookup(self)
+    }
+}
+
+impl FromStr for RefrigerantName {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        name::canonicalize(s).map(RefrigerantName)
     }
 }
 
@@ -267,6 +501,86 @@ impl TryFrom<String> for RefrigerantName {
     type Error = String;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        Self::new(&value).ok_or("Unable to parse refrigerant name".into())
+        value.parse()
+    }
+}
+
+/// Shorthands for building readings in tests.
+#[cfg(test)]
+pub(crate) mod test_util {
+    use crate::{CompositionBasis, GCReading, RefrigerantName};
+
+    pub(crate) fn name(name: &str) -> RefrigerantName {
+        name.parse().unwrap()
+    }
+
+    /// A reading on a mass basis.
+    pub(crate) fn reading(components: &[(&str, f64)]) -> GCReading {
+        GCReading::new(
+            components.iter().map(|(n, v)| (name(n), *v)).collect(),
+            CompositionBasis::Mass,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::name;
+
+    use super::*;
+
+    #[test]
+    fn strictest_accepting_classification() {
+        let classifications = ClassificationList(vec![
+            ("Mix 410a".into(), RefrigerantClassification::new(0.5, None)),
+            (
+                "R-410aM".into(),
+                RefrigerantClassification::new(0.98, Some(0.005)),
+            ),
+            (
+                "R-410a/R-32".into(),
+                RefrigerantClassification::new(0.99, None).with_mixed(name("r-32"), 0.02),
+            ),
+        ]);
+        let origin = name("r-410a");
+        let none = HashMap::new();
+
+        assert_eq!(
+            classifications.get_classification(&origin, 0.985, 0.001, &none),
+            "R-410aM"
+        );
+        assert_eq!(
+            classifications.get_classification(&origin, 0.985, 0.01, &none),
+            "Mix 410a"
+        );
+        assert_eq!(
+            classifications.get_classification(
+                &origin,
+                0.975,
+                0.001,
+                &HashMap::from([(name("r-32"), 0.025)])
+            ),
+            "R-410a/R-32"
+        );
+        assert_eq!(
+            classifications.get_classification(&origin, 0.2, 0., &none),
+            DEFAULT_LABEL
+        );
+
+        let pure = ClassificationList::default();
+        assert_eq!(pure.get_classification(&origin, 0.999, 0., &none), "R-410A");
+        assert_eq!(
+            pure.get_classification(&origin, 0.99, 0., &none),
+            DEFAULT_LABEL
+        );
+    }
+
+    #[test]
+    fn reading_from_string() {
+        let reading = GCReading::try_from("r-32 0.5, r-125 0.5".to_string()).unwrap();
+        assert_eq!(reading.get_component(&name("r-32")), Some(&0.5));
+
+        assert!(GCReading::try_from("r-22".to_string()).is_err());
+        assert!(GCReading::try_from("r-22 x".to_string()).is_err());
     }
 }
@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::RefrigerantName;
+
+/// Designation prefixes accepted in front of an ASHRAE 34 number, longest first so that
+/// "HCFC" is not mistaken for "HC".
+const PREFIXES: [&str; 9] = ["HCFO", "HCFC", "HFO", "HFC", "CFC", "PFC", "HC", "FC", "R"];
+
+/// Number of trailing isomer letters allowed (e.g. the "mzz" in R-1336mzz(Z)).
+const MAX_ISOMER_SUFFIX: usize = 3;
+
+/// Table of trade names and other non-ASHRAE spellings, resolved before designation parsing.
+#[derive(Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(try_from = "HashMap<String, AliasTarget>")]
+pub struct AliasTable(HashMap<String, AliasTarget>);
+
+/// What an alias stands for, written as a name or a list of names in JSON.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum AliasTarget {
+    Name(RefrigerantName),
+    /// Peak label of compounds the method cannot separate, e.g. "R-115/R-143".
+    CoElution(Vec<RefrigerantName>),
+}
+
+/// Parses an ASHRAE 34 designation into its canonical spelling.
+///
+/// Accepts the usual prefixes (R, HFC, HCFC, CFC, HFO, ...), optional separators and any casing.
+/// Blends (4xx/5xx) and inorganics (7xx) get an upper case letter suffix, everything else a lower
+/// case isomer suffix, so "hfc134A", "R 134a" and "134a" all become "R-134a".
+pub(crate) fn canonicalize(name: &str) -> Result<String, String> {
+    let compact: String = name.trim().chars().filter(|c| !c.is_whitespace()).collect();
+
+    let upper = compact.to_ascii_uppercase();
+
+    let prefix_len = PREFIXES
+        .iter()
+        .find(|p| upper.starts_with(*p))
+        .map_or(0, |p| p.len());
+
+    // ascii uppercase preserves byte offsets, so `compact` can be sliced with them
+    let mut rest = compact[prefix_len..].trim_start_matches('-');
+
+    let cyclic =
+        rest.starts_with(['C', 'c']) && rest[1..].starts_with(|c: char| c.is_ascii_digit());
+    if cyclic {
+        rest = &rest[1..];
+    }
+
+    let digits_len = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    let (number, rest) = rest.split_at(digits_len);
+
+    if number.is_empty() || number.starts_with('0') {
+        return Err(format!("\"{name}\" does not contain a refrigerant number."));
+    }
+
+    let suffix_len = rest
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(rest.len());
+    let (suffix, rest) = rest.split_at(suffix_len);
+
+    let stereo = match rest.to_ascii_uppercase().as_str() {
+        "" => "",
+        "(E)" => "(E)",
+        "(Z)" => "(Z)",
+        _ => {
+            return Err(format!(
+                "\"{name}\" has unexpected trailing characters \"{rest}\"."
+            ));
+        }
+    };
+
+    let series = number.parse::<u32>().map_err(|e| e.to_string())?;
+    let lettered_series = number.len() == 3 && matches!(series / 100, 4 | 5 | 7);
+
+    let suffix = if lettered_series {
+        if suffix.len() > 1 || !stereo.is_empty() {
+            return Err(format!(
+                "\"{name}\" is not a valid {}xx designation.",
+                series / 100
+            ));
+        }
+
+        suffix.to_ascii_uppercase()
+    } else {
+        if suffix.len() > MAX_ISOMER_SUFFIX {
+            return Err(format!(
+                "\"{name}\" has an invalid isomer suffix \"{suffix}\"."
+            ));
+        }
+
+        suffix.to_ascii_lowercase()
+    };
+
+    Ok(format!(
+        "R-{}{}{}{}",
+        if cyclic { "C" } else { "" },
+        number,
+        suffix,
+        stereo
+    ))
+}
+
+fn alias_key(alias: &str) -> String {
+    alias
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect::<String>()
+        .to_uppercase()
+}
+
+impl AliasTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, alias: &str, name: RefrigerantName) {
+        self.0.insert(alias_key(alias), AliasTarget::Name(name));
+    }
+
+    /// Adds a peak label that stands for several co-eluting compounds.
+    pub fn insert_coelution(&mut self, alias: &str, names: Vec<RefrigerantName>) {
+        self.0
+            .insert(alias_key(alias), AliasTarget::CoElution(names));
+    }
+
+    /// Resolves a name through the alias table first, then as an ASHRAE designation. A
+    /// co-elution is an error, see [`Self::resolve_all`].
+    pub fn resolve(&self, name: &str) -> Result<RefrigerantName, String> {
+        match self.resolve_all(name)?.as_slice() {
+            [single] => Ok(single.clone()),
+            names => Err(format!(
+                "\"{name}\" is a co-elution of {}.",
+                names
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+
+    /// Like [`Self::resolve`], but resolves a co-elution to all of its compounds.
+    pub fn resolve_all(&self, name: &str) -> Result<Vec<RefrigerantName>, String> {
+        match self.0.get(&alias_key(name)) {
+            Some(AliasTarget::Name(resolved)) => Ok(vec![resolved.clone()]),
+            Some(AliasTarget::CoElution(names)) => Ok(names.clone()),
+            None => Ok(vec![name.parse()?]),
+        }
+    }
+}
+
+impl TryFrom<HashMap<String, AliasTarget>> for AliasTable {
+    type Error = String;
+
+    fn try_from(value: HashMap<String, AliasTarget>) -> Result<Self, Self::Error> {
+        let mut table = Self::new();
+
+        for (alias, target) in value {
+            if table.0.contains_key(&alias_key(&alias)) {
+                return Err(format!("Alias \"{alias}\" is listed more than once."));
+            }
+
+            if let AliasTarget::CoElution(names) = &target
+                && names.len() < 2
+            {
+                return Err(format!(
+                    "Co-elution \"{alias}\" needs at least two compounds."
+                ));
+            }
+
+            table.0.insert(alias_key(&alias), target);
+        }
+
+        Ok(table)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn canon(name: &str) -> String {
+        canonicalize(name).unwrap()
+    }
+
+    #[test]
+    fn prefixes_and_spacing() {
+        for name in ["R-134a", "R134a", "r-134A", "HFC-134a", "hfc 134a", "134a"] {
+            assert_eq!(canon(name), "R-134a", "{name}");
+        }
+
+        assert_eq!(canon("HCFC-22"), "R-22");
+        assert_eq!(canon("HC-290"), "R-290");
+        assert_eq!(canon("RC318"), "R-C318");
+    }
+
+    #[test]
+    fn blend_series() {
+        assert_eq!(canon("r-410a"), "R-410A");
+        assert_eq!(canon("R-507"), "R-507");
+        assert_eq!(canon("R-744"), "R-744");
+        assert!(canonicalize("R-404AB").is_err());
+    }
+
+    #[test]
+    fn isomers() {
+        assert_eq!(canon("R-1234YF"), "R-1234yf");
+        assert_eq!(canon("HFO-1234ze(e)"), "R-1234ze(E)");
+        assert_eq!(canon("R-1336mzz(Z)"), "R-1336mzz(Z)");
+        assert_eq!(canon("R-142B"), "R-142b");
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        for name in [
+            "",
+            "R-",
+            "Freon",
+            "R-115/R-143",
+            "R-0134a",
+            "R-134a (blend)",
+        ] {
+            assert!(canonicalize(name).is_err(), "{name}");
+        }
+    }
+
+    #[test]
+    fn aliases() {
+        let table: AliasTable = serde_json::from_str(r#"{ "Opteon XP40": "r-449a" }"#).unwrap();
+
+        assert_eq!(table.resolve("opteon xp-40").unwrap().to_string(), "R-449A");
+        assert_eq!(table.resolve("r-22").unwrap().to_string(), "R-22");
+        assert!(table.resolve("Opteon").is_err());
+
+        let table: AliasTable =
+            serde_json::from_str(r#"{ "R-115/R-143": ["r-115", "r-143"] }"#).unwrap();
+        assert_eq!(
+            table.resolve_all("r-115 / r-143").unwrap(),
+            ["R-115".parse().unwrap(), "R-143".parse().unwrap()]
+        );
+        assert!(table.resolve("R-115/R-143").is_err());
+        assert!(serde_json::from_str::<AliasTable>(r#"{ "R-115/R-143": ["r-115"] }"#).is_err());
+    }
+}
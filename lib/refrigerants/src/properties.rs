@@ -0,0 +1,156 @@
+use std::fmt::Display;
+
+use crate::RefrigerantName;
+
+/// ASHRAE 34 safety group: toxicity (A/B) followed by flammability (1, 2L, 2, 3).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SafetyClass {
+    A1,
+    A2L,
+    A2,
+    A3,
+    B1,
+    B2L,
+    B2,
+    B3,
+}
+
+/// IPCC assessment report a GWP value is taken from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Assessment {
+    AR4,
+    AR5,
+    AR6,
+}
+
+/// 100-year global warming potentials.
+///
+/// AR4 did not assess the HFOs; their AR4 column holds the values regulators commonly quote.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gwp {
+    pub ar4: f64,
+    pub ar5: f64,
+    pub ar6: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefrigerantProperties {
+    /// Canonical ASHRAE designation, as produced by `RefrigerantName`.
+    pub name: &'static str,
+    pub formula: &'static str,
+    /// g/mol
+    pub molar_mass: f64,
+    /// Normal boiling point in °C (sublimation point for R-744).
+    pub boiling_point: f64,
+    /// `None` for substances without an ASHRAE 34 classification.
+    pub safety_class: Option<SafetyClass>,
+    pub gwp: Gwp,
+    pub odp: f64,
+}
+
+macro_rules! refrigerant {
+    ($name:literal, $formula:literal, $mm:literal, $bp:literal, $class:expr, [$ar4:literal, $ar5:literal, $ar6:literal], $odp:literal) => {
+        RefrigerantProperties {
+            name: $name,
+            formula: $formula,
+            molar_mass: $mm,
+            boiling_point: $bp,
+            safety_class: $class,
+            gwp: Gwp {
+                ar4: $ar4,
+                ar5: $ar5,
+                ar6: $ar6,
+            },
+            odp: $odp,
+        }
+    };
+}
+
+use SafetyClass::*;
+
+// name, formula, molar mass, boiling point, safety class, [GWP AR4, AR5, AR6], ODP
+#[rustfmt::skip]
+static REFRIGERANTS: &[RefrigerantProperties] = &[
+    refrigerant!("R-11",        "CCl3F",    137.37,   23.7, Some(A1),   [4750.,  4660.,  6230.], 1.0),
+    refrigerant!("R-12",        "CCl2F2",   120.91,  -29.8, Some(A1),  [10900., 10200., 12500.], 1.0),
+    refrigerant!("R-13",        "CClF3",    104.46,  -81.3, Some(A1),  [14400., 13900., 16200.], 1.0),
+    refrigerant!("R-14",        "CF4",       88.00, -128.0, Some(A1),   [7390.,  6630.,  7380.], 0.0),
+    refrigerant!("R-22",        "CHClF2",    86.47,  -40.8, Some(A1),   [1810.,  1760.,  1960.], 0.055),
+    refrigerant!("R-23",        "CHF3",      70.01,  -82.0, Some(A1),  [14800., 12400., 14600.], 0.0),
+    refrigerant!("R-32",        "CH2F2",     52.02,  -51.7, Some(A2L),   [675.,   677.,   771.], 0.0),
+    refrigerant!("R-113",       "C2Cl3F3",  187.38,   47.6, Some(A1),   [6130.,  5820.,  6520.], 0.8),
+    refrigerant!("R-114",       "C2Cl2F4",  170.92,    3.6, Some(A1),  [10000.,  8590.,  9430.], 1.0),
+    refrigerant!("R-115",       "C2ClF5",   154.47,  -39.1, Some(A1),   [7370.,  7670.,  9600.], 0.6),
+    refrigerant!("R-116",       "C2F6",     138.01,  -78.1, Some(A1),  [12200., 11100., 12400.], 0.0),
+    refrigerant!("R-123",       "C2HCl2F3", 152.93,   27.8, Some(B1),     [77.,    79.,    90.], 0.02),
+    refrigerant!("R-124",       "C2HClF4",  136.48,  -12.0, Some(A1),    [609.,   527.,   597.], 0.022),
+    refrigerant!("R-125",       "C2HF5",    120.02,  -48.1, Some(A1),   [3500.,  3170.,  3740.], 0.0),
+    refrigerant!("R-134a",      "C2H2F4",   102.03,  -26.1, Some(A1),   [1430.,  1300.,  1530.], 0.0),
+    refrigerant!("R-142b",      "C2H3ClF2", 100.50,   -9.1, Some(A2),   [2310.,  1980.,  2300.], 0.065),
+    refrigerant!("R-143",       "C2H3F3",    84.04,    5.0, None,        [353.,   328.,   364.], 0.0),
+    refrigerant!("R-143a",      "C2H3F3",    84.04,  -47.2, Some(A2L),  [4470.,  4800.,  5810.], 0.0),
+    refrigerant!("R-152a",      "C2H4F2",    66.05,  -24.0, Some(A2),    [124.,   138.,   164.], 0.0),
+    refrigerant!("R-170",       "C2H6",      30.07,  -88.6, Some(A3),      [6.,     6.,     6.], 0.0),
+    refrigerant!("R-218",       "C3F8",     188.02,  -36.8, Some(A1),   [8830.,  8900.,  9290.], 0.0),
+    refrigerant!("R-227ea",     "C3HF7",    170.03,  -16.3, Some(A1),   [3220.,  3350.,  3600.], 0.0),
+    refrigerant!("R-245fa",     "C3H3F5",   134.05,   15.1, Some(B1),   [1030.,   858.,   962.], 0.0),
+    refrigerant!("R-290",       "C3H8",      44.10,  -42.1, Some(A3),      [3.,     3.,   0.02], 0.0),
+    refrigerant!("R-C318",      "C4F8",     200.03,   -6.0, Some(A1),  [10300.,  9540., 10200.], 0.0),
+    refrigerant!("R-600",       "C4H10",     58.12,   -0.5, Some(A3),      [4.,     4.,     4.], 0.0),
+    refrigerant!("R-600a",      "C4H10",     58.12,  -11.7, Some(A3),      [3.,     3.,     3.], 0.0),
+    refrigerant!("R-601",       "C5H12",     72.15,   36.1, Some(A3),      [5.,     5.,     5.], 0.0),
+    refrigerant!("R-601a",      "C5H12",     72.15,   27.8, Some(A3),      [5.,     5.,     5.], 0.0),
+    refrigerant!("R-717",       "NH3",       17.03,  -33.3, Some(B2L),     [0.,     0.,     0.], 0.0),
+    refrigerant!("R-744",       "CO2",       44.01,  -78.4, Some(A1),      [1.,     1.,     1.], 0.0),
+    refrigerant!("R-1233zd(E)", "C3H2ClF3", 130.50,   18.3, Some(A1),     [4.5,     1.,   3.88], 0.00034),
+    refrigerant!("R-1234yf",    "C3H2F4",   114.04,  -29.5, Some(A2L),     [4.,     1.,  0.501], 0.0),
+    refrigerant!("R-1234ze(E)", "C3H2F4",   114.04,  -19.0, Some(A2L),     [7.,     1.,   1.37], 0.0),
+    refrigerant!("R-1270",      "C3H6",      42.08,  -47.7, Some(A3),      [2.,     2.,     2.], 0.0),
+];
+
+/// Looks up the embedded properties of a pure refrigerant.
+pub fn lookup(name: &RefrigerantName) -> Option<&'static RefrigerantProperties> {
+    REFRIGERANTS.iter().find(|r| r.name == name.as_ref())
+}
+
+/// Every refrigerant in the embedded database.
+pub fn all() -> impl Iterator<Item = &'static RefrigerantProperties> {
+    REFRIGERANTS.iter()
+}
+
+impl Gwp {
+    pub fn get(&self, assessment: Assessment) -> f64 {
+        match assessment {
+            Assessment::AR4 => self.ar4,
+            Assessment::AR5 => self.ar5,
+            Assessment::AR6 => self.ar6,
+        }
+    }
+}
+
+impl Display for SafetyClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn names_are_canonical() {
+        for r in all() {
+            assert_eq!(RefrigerantName::new(r.name).unwrap().as_ref(), r.name);
+        }
+    }
+
+    #[test]
+    fn lookup_by_any_spelling() {
+        let r134a = lookup(&"HFC-134A".parse().unwrap()).unwrap();
+
+        assert_eq!(r134a.safety_class, Some(SafetyClass::A1));
+        assert_eq!(r134a.gwp.get(Assessment::AR4), 1430.);
+        assert!(lookup(&"R-9999".parse().unwrap()).is_none());
+    }
+}
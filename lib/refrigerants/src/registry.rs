@@ -0,0 +1,156 @@
+use std::{collections::HashMap, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ClassificationList, RefrigerantMixture, RefrigerantName};
+
+/// Handle of a mixture in a [`MixtureRegistry`], only meaningful for the registry that issued
+/// it.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct MixtureId(usize);
+
+/// Owns the configured mixtures and hands out shared references to them. Cloning the registry
+/// or its mixtures only bumps reference counts, so both can be moved to worker threads.
+#[derive(Debug, Clone, Default)]
+pub struct MixtureRegistry {
+    mixtures: Vec<Arc<RefrigerantMixture>>,
+    ids: HashMap<RefrigerantName, MixtureId>,
+}
+
+impl MixtureRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `mixture`, failing if one with the same identifier is already registered.
+    pub fn insert(&mut self, mixture: RefrigerantMixture) -> Result<MixtureId, String> {
+        if self.ids.contains_key(mixture.identifier()) {
+            return Err(format!(
+                "Mixture {} is already registered.",
+                mixture.identifier()
+            ));
+        }
+
+        let id = MixtureId(self.mixtures.len());
+        self.ids.insert(mixture.identifier().clone(), id);
+        self.mixtures.push(Arc::new(mixture));
+
+        Ok(id)
+    }
+
+    /// Registers a single component mixture for every refrigerant in `names` that is not
+    /// registered yet.
+    pub fn insert_pure<'n>(&mut self, names: impl IntoIterator<Item = &'n RefrigerantName>) {
+        for name in names {
+            if !self.ids.contains_key(name) {
+                self.insert(RefrigerantMixture::new(
+                    name.clone(),
+                    HashMap::from([(name.clone(), 1.)]),
+                    ClassificationList::default(),
+                ))
+                .expect("checked above");
+            }
+        }
+    }
+
+    pub fn id(&self, identifier: &RefrigerantName) -> Option<MixtureId> {
+        self.ids.get(identifier).copied()
+    }
+
+    pub fn get(&self, id: MixtureId) -> Option<&Arc<RefrigerantMixture>> {
+        self.mixtures.get(id.0)
+    }
+
+    pub fn by_name(&self, identifier: &RefrigerantName) -> Option<&Arc<RefrigerantMixture>> {
+        self.id(identifier).and_then(|id| self.get(id))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (MixtureId, &Arc<RefrigerantMixture>)> {
+        self.mixtures
+            .iter()
+            .enumerate()
+            .map(|(i, mix)| (MixtureId(i), mix))
+    }
+
+    pub fn mixtures(&self) -> &[Arc<RefrigerantMixture>] {
+        &self.mixtures
+    }
+
+    pub fn len(&self) -> usize {
+        self.mixtures.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mixtures.is_empty()
+    }
+}
+
+impl TryFrom<Vec<RefrigerantMixture>> for MixtureRegistry {
+    type Error = String;
+
+    fn try_from(value: Vec<RefrigerantMixture>) -> Result<Self, Self::Error> {
+        let mut registry = Self::new();
+
+        for mixture in value {
+            registry.insert(mixture)?;
+        }
+
+        Ok(registry)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::thread;
+
+    use crate::{CompositionBasis, GCReading};
+
+    use super::*;
+
+    fn r410a() -> RefrigerantMixture {
+        serde_json::from_str(
+            r#"{
+                "identifier": "r-410a",
+                "components": { "r-32": 0.5, "r-125": 0.5 },
+                "tolerances": { "r-32": 0.01, "r-125": 0.01 },
+                "classifications": { "Clean": { "purity": 0.995, "max_lows": 0.005 } }
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn handles_across_threads() {
+        let mut registry = MixtureRegistry::new();
+        let id = registry.insert(r410a()).unwrap();
+        registry.insert_pure(&["R-32".parse().unwrap(), "R-22".parse().unwrap()]);
+
+        assert!(registry.insert(r410a()).is_err());
+        assert_eq!(registry.len(), 3);
+
+        let shared = registry.clone();
+        let identifier = thread::spawn(move || shared.get(id).unwrap().identifier().clone())
+            .join()
+            .unwrap();
+
+        assert_eq!(&identifier, registry.get(id).unwrap().identifier());
+        assert!(registry.by_name(&"r-22".parse().unwrap()).is_some());
+    }
+
+    #[test]
+    fn serialization_round_trip() {
+        let mixture = r410a();
+        let json = serde_json::to_string(&mixture).unwrap();
+        assert_eq!(
+            serde_json::from_str::<RefrigerantMixture>(&json).unwrap(),
+            mixture
+        );
+
+        let reading = GCReading::new(
+            HashMap::from([("R-32".parse().unwrap(), 0.5)]),
+            CompositionBasis::Mass,
+        );
+        let json = serde_json::to_string(&reading).unwrap();
+        assert_eq!(serde_json::from_str::<GCReading>(&json).unwrap(), reading);
+    }
+}
@@ -0,0 +1,302 @@
+use std::{collections::HashMap, fmt::Display};
+
+use serde::Deserialize;
+
+use crate::{CompositionBasis, GCReading, RefrigerantMixture, RefrigerantName};
+
+/// A single limit of a purity specification. Fractions are by mass.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "criterion", rename_all = "snake_case")]
+pub enum Criterion {
+    /// Minimum total of the target mixture's components.
+    Purity { min: f64 },
+    /// Maximum total of refrigerants that are not part of the target mixture.
+    OtherRefrigerants { max: f64 },
+    /// Every blend component within the mixture's tolerances. Skipped for pure refrigerants,
+    /// an error for blends without tolerances.
+    Composition,
+    /// Maximum of a lab value the GC cannot measure (water, acidity, residue, ...), looked up
+    /// by `name` in the measurements passed to [`Specification::evaluate`].
+    Measurement {
+        name: String,
+        max: f64,
+        unit: String,
+    },
+}
+
+/// A named table of criteria a reading is certified against.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Specification {
+    pub name: String,
+    pub criteria: Vec<Criterion>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Limit {
+    Min(f64),
+    Max(f64),
+    Range(f64, f64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CriterionResult {
+    pub label: String,
+    /// `None` if the value needed for the criterion was not supplied.
+    pub measured: Option<f64>,
+    pub limit: Limit,
+    pub unit: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpecReport {
+    pub specification: String,
+    pub mixture: RefrigerantName,
+    pub results: Vec<CriterionResult>,
+}
+
+impl Specification {
+    /// AHRI Standard 700 limits for reclaimed refrigerant.
+    pub fn ahri_700() -> Self {
+        let measurement = |name: &str, max: f64, unit: &str| Criterion::Measurement {
+            name: name.into(),
+            max,
+            unit: unit.into(),
+        };
+
+        Self {
+            name: "AHRI 700".into(),
+            criteria: vec![
+                Criterion::Purity { min: 0.995 },
+                Criterion::OtherRefrigerants { max: 0.005 },
+                Criterion::Composition,
+                measurement("water", 10., "ppm by weight"),
+                measurement("acidity", 1., "ppm by weight"),
+                measurement("high_boiling_residue", 0.0001, "by volume"),
+                measurement("non_condensables", 0.015, "by volume"),
+            ],
+        }
+    }
+
+    /// Evaluates `reading` as a sample of `mixture` against every criterion.
+    ///
+    /// Refrigerant fractions are taken as absolute fractions of the sample, so a reading that
+    /// does not add up to one counts the remainder against purity.
+    pub fn evaluate(
+        &self,
+        reading: &GCReading,
+        mixture: &RefrigerantMixture,
+        measurements: &HashMap<String, f64>,
+    ) -> Result<SpecReport, String> {
+        let reading = reading.to_basis(CompositionBasis::Mass)?;
+        let components = mixture.component_set();
+
+        let (purity, others) =
+            reading
+                .components()
+                .fold((0., 0.), |(purity, others), (name, &v)| {
+                    if components.contains(name) {
+                        (purity + v, others)
+                    } else {
+                        (purity, others + v)
+                    }
+                });
+
+        let mut results = vec![];
+
+        for criterion in &self.criteria {
+            match criterion {
+                Criterion::Purity { min } => results.push(CriterionResult {
+                    label: format!("{} purity", mixture.identifier()),
+                    measured: Some(purity),
+                    limit: Limit::Min(*min),
+                    unit: "by weight".into(),
+                }),
+                Criterion::OtherRefrigerants { max } => results.push(CriterionResult {
+                    label: "Other refrigerants".into(),
+                    measured: Some(others),
+                    limit: Limit::Max(*max),
+                    unit: "by weight".into(),
+                }),
+                Criterion::Composition if components.len() > 1 => {
+                    let check = mixture.check_composition(&reading)?;
+
+                    results.extend(check.components.into_iter().map(|c| CriterionResult {
+                        label: format!("{} in {}", c.name, mixture.identifier()),
+                        measured: Some(c.measured),
+                        limit: Limit::Range(
+                            c.nominal - c.tolerance.minus,
+                            c.nominal + c.tolerance.plus,
+                        ),
+                        unit: "by weight of blend".into(),
+                    }));
+                }
+                Criterion::Composition => {}
+                Criterion::Measurement { name, max, unit } => results.push(CriterionResult {
+                    label: name.clone(),
+                    measured: measurements.get(name).copied(),
+                    limit: Limit::Max(*max),
+                    unit: unit.clone(),
+                }),
+            }
+        }
+
+        Ok(SpecReport {
+            specification: self.name.clone(),
+            mixture: mixture.identifier().clone(),
+            results,
+        })
+    }
+}
+
+impl Limit {
+    pub fn admits(&self, value: f64) -> bool {
+        match *self {
+            Limit::Min(min) => value >= min,
+            Limit::Max(max) => value <= max,
+            Limit::Range(min, max) => (min..=max).contains(&value),
+        }
+    }
+}
+
+impl CriterionResult {
+    /// `None` when the criterion could not be evaluated.
+    pub fn passed(&self) -> Option<bool> {
+        self.measured.map(|m| self.limit.admits(m))
+    }
+}
+
+impl SpecReport {
+    /// `Some(false)` as soon as any criterion fails, `None` if nothing failed but some
+    /// criteria could not be evaluated.
+    pub fn passed(&self) -> Option<bool> {
+        let passed: Vec<_> = self.results.iter().map(CriterionResult::passed).collect();
+
+        if passed.contains(&Some(false)) {
+            Some(false)
+        } else if passed.contains(&None) {
+            None
+        } else {
+            Some(true)
+        }
+    }
+}
+
+impl Display for Limit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Limit::Min(min) => write!(f, ">= {}", min),
+            Limit::Max(max) => write!(f, "<= {}", max),
+            Limit::Range(min, max) => write!(f, "{:.4} - {:.4}", min, max),
+        }
+    }
+}
+
+impl Display for SpecReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} as {}: {}",
+            self.specification,
+            self.mixture,
+            match self.passed() {
+                Some(true) => "PASS",
+                Some(false) => "FAIL",
+                None => "INCOMPLETE",
+            }
+        )?;
+
+        for r in &self.results {
+            write!(
+                f,
+                "\n  {}: measured {}, limit {} {} [{}]",
+                r.label,
+                r.measured.map_or("-".into(), |m| format!("{:.4}", m)),
+                r.limit,
+                r.unit,
+                match r.passed() {
+                    Some(true) => "pass",
+                    Some(false) => "fail",
+                    None => "not measured",
+                }
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::reading;
+
+    use super::*;
+
+    fn r410a() -> RefrigerantMixture {
+        serde_json::from_str(
+            r#"{
+                "identifier": "r-410a",
+                "components": { "r-32": 0.5, "r-125": 0.5 },
+                "tolerances": {
+                    "r-32": { "plus": 0.005, "minus": 0.015 },
+                    "r-125": { "plus": 0.015, "minus": 0.005 }
+                }
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn clean_sample_passes() {
+        let measurements = HashMap::from([
+            ("water".into(), 4.),
+            ("acidity".into(), 0.2),
+            ("high_boiling_residue".into(), 0.),
+            ("non_condensables".into(), 0.004),
+        ]);
+
+        let report = Specification::ahri_700()
+            .evaluate(
+                &reading(&[("R-32", 0.499), ("R-125", 0.4995), ("R-22", 0.0015)]),
+                &r410a(),
+                &measurements,
+            )
+            .unwrap();
+
+        assert_eq!(report.passed(), Some(true), "{}", report);
+    }
+
+    #[test]
+    fn other_refrigerants_fail() {
+        let report = Specification::ahri_700()
+            .evaluate(
+                &reading(&[("R-32", 0.495), ("R-125", 0.495), ("R-22", 0.01)]),
+                &r410a(),
+                &HashMap::new(),
+            )
+            .unwrap();
+
+        assert_eq!(report.passed(), Some(false));
+
+        let failed: Vec<_> = report
+            .results
+            .iter()
+            .filter(|r| r.passed() == Some(false))
+            .map(|r| r.label.as_str())
+            .collect();
+
+        assert_eq!(failed, vec!["R-410A purity", "Other refrigerants"]);
+    }
+
+    #[test]
+    fn missing_lab_values_are_incomplete() {
+        let report = Specification::ahri_700()
+            .evaluate(
+                &reading(&[("R-32", 0.5), ("R-125", 0.5)]),
+                &r410a(),
+                &HashMap::new(),
+            )
+            .unwrap();
+
+        assert_eq!(report.passed(), None);
+    }
+}
@@ -0,0 +1,189 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::RefrigerantName;
+
+/// Allowed deviation from a nominal blend fraction, in the same units as the fraction.
+///
+/// Deserializes from a single number for symmetric bands (`0.02` for ±2%) or from
+/// `{ "plus": .., "minus": .. }` for the asymmetric bands ASHRAE 34 uses on some blends.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(from = "ToleranceDef")]
+pub struct Tolerance {
+    pub plus: f64,
+    pub minus: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ToleranceDef {
+    Symmetric(f64),
+    Asymmetric { plus: f64, minus: f64 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentDeviation {
+    pub name: RefrigerantName,
+    pub nominal: f64,
+    pub measured: f64,
+    pub tolerance: Tolerance,
+}
+
+/// Result of comparing a reading against a blend's nominal composition and tolerances.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompositionCheck {
+    pub mixture: RefrigerantName,
+    pub components: Vec<ComponentDeviation>,
+}
+
+impl From<ToleranceDef> for Tolerance {
+    fn from(value: ToleranceDef) -> Self {
+        match value {
+            ToleranceDef::Symmetric(t) => Self::symmetric(t),
+            ToleranceDef::Asymmetric { plus, minus } => Self { plus, minus },
+        }
+    }
+}
+
+impl Tolerance {
+    pub fn symmetric(tolerance: f64) -> Self {
+        Self {
+            plus: tolerance,
+            minus: tolerance,
+        }
+    }
+
+    /// Whether `deviation` (measured - nominal) lies within the band.
+    pub fn contains(&self, deviation: f64) -> bool {
+        // fractions come out of unit conversions, don't fail a cylinder on rounding noise
+        const EPSILON: f64 = 1e-9;
+
+        deviation <= self.plus + EPSILON && deviation >= -self.minus - EPSILON
+    }
+}
+
+impl ComponentDeviation {
+    pub fn deviation(&self) -> f64 {
+        self.measured - self.nominal
+    }
+
+    pub fn in_spec(&self) -> bool {
+        self.tolerance.contains(self.deviation())
+    }
+}
+
+impl CompositionCheck {
+    pub fn in_spec(&self) -> bool {
+        self.components.iter().all(ComponentDeviation::in_spec)
+    }
+}
+
+impl Display for Tolerance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.plus == self.minus {
+            write!(f, "±{:.1}%", self.plus * 100.)
+        } else {
+            write!(f, "+{:.1}/-{:.1}%", self.plus * 100., self.minus * 100.)
+        }
+    }
+}
+
+impl Display for CompositionCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {}",
+            self.mixture,
+            if self.in_spec() {
+                "in spec"
+            } else {
+                "OUT OF SPEC"
+            }
+        )?;
+
+        for c in &self.components {
+            write!(
+                f,
+                "\n  {}: nominal {:.2}%, measured {:.2}%, deviation {:+.2}% ({}){}",
+                c.name,
+                c.nominal * 100.,
+                c.measured * 100.,
+                c.deviation() * 100.,
+                c.tolerance,
+                if c.in_spec() { "" } else { " !" }
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::{
+        CompositionBasis, GCReading, RefrigerantMixture,
+        test_util::{name, reading},
+    };
+
+    fn r404a() -> RefrigerantMixture {
+        serde_json::from_str(
+            r#"{
+                "identifier": "r-404a",
+                "components": { "r-125": 0.44, "r-143a": 0.52, "r-134a": 0.04 },
+                "tolerances": { "r-125": 0.02, "r-143a": { "plus": 0.01, "minus": 0.01 }, "r-134a": 0.02 }
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn nominal_is_in_spec() {
+        let nominal = reading(&[("R-125", 0.44), ("R-143a", 0.52), ("R-134a", 0.04)]);
+        let check = r404a().check_composition(&nominal).unwrap();
+
+        assert!(check.in_spec());
+        assert_eq!(check.components.len(), 3);
+    }
+
+    #[test]
+    fn tight_component_out_of_spec() {
+        let off = reading(&[("R-125", 0.425), ("R-143a", 0.535), ("R-134a", 0.04)]);
+        let check = r404a().check_composition(&off).unwrap();
+
+        let failed: Vec<_> = check
+            .components
+            .iter()
+            .filter(|c| !c.in_spec())
+            .map(|c| c.name.to_string())
+            .collect();
+
+        assert_eq!(failed, vec!["R-143a"]);
+    }
+
+    #[test]
+    fn contaminants_are_normalized_out() {
+        let contaminated = reading(&[
+            ("R-125", 0.43),
+            ("R-143a", 0.51),
+            ("R-134a", 0.04),
+            ("R-22", 0.02),
+        ]);
+
+        assert!(r404a().check_composition(&contaminated).unwrap().in_spec());
+
+        // no molar mass is known for the contaminant, only the blend needs converting
+        let mut unknown = r404a()
+            .fractions(CompositionBasis::Mole)
+            .unwrap()
+            .into_iter()
+            .map(|(name, v)| (name, v * 0.98))
+            .collect::<HashMap<_, _>>();
+        unknown.insert(name("R-1279"), 0.02);
+        let unknown = GCReading::new(unknown, CompositionBasis::Mole);
+
+        assert!(r404a().check_composition(&unknown).unwrap().in_spec());
+    }
+}
@@ -1,48 +1,562 @@
-use std::{fs::File, path::Path};
+use std::{collections::HashMap, fmt::Display, fs::File, io::BufReader, path::Path};
 
 use itertools::Itertools;
 use nalgebra::DVector;
-use serde_json::Value;
+use serde::{
+    Deserialize, Deserializer, Serialize,
+    de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor},
+};
 
+mod andi;
+mod netcdf;
+mod text;
+
+pub use andi::read_andi;
+pub use text::{Column, TextFormat, TimeUnit, read_delimited};
+
+/// Reads just the signal of a single detector `.fusion-data` file, see [`read_run`] for the rest.
 pub fn read_series<'a>(path: impl AsRef<Path>) -> Result<DVector<f64>, ReadError<'a>> {
     let file = File::open(path).map_err(ReadError::IOError)?;
-    let value: Value = serde_json::from_reader(file).map_err(ReadError::ParseError)?;
-
-    let data = value
-        .get("detectors")
-        .ok_or(ReadError::Other("No detectors."))?
-        .as_object()
-        .ok_or(ReadError::Other("Invalid data format."))?
-        .iter()
+    let series: SeriesDef =
+        serde_json::from_reader(BufReader::new(file)).map_err(ReadError::ParseError)?;
+
+    let detector = series
+        .detectors
+        .into_values()
         .at_most_one()
         .map_err(|_| ReadError::Other("More than one detector."))?
-        .map(|(_, val)| val)
-        .ok_or(ReadError::Other("No detectors."))?
-        .get("values")
-        .ok_or(ReadError::Other("No values read."))?
-        .as_array()
-        .ok_or(ReadError::Other("values property is not an array."))?
-        .iter()
-        .map(|v| v.as_f64())
-        .collect::<Option<Vec<f64>>>()
-        .ok_or(ReadError::Other("Failed to read all datapoints."))?;
+        .ok_or(ReadError::Other("No detectors."))?;
+
+    Ok(DVector::from_vec(detector.values))
+}
+
+/// A Fusion run with its metadata and the peaks the instrument integrated.
+#[derive(Debug, Clone)]
+pub struct Run {
+    /// Unique id the instrument gave the run.
+    pub id: String,
+    /// Sample name entered on the instrument.
+    pub name: String,
+    /// Start of the run as recorded by the instrument, in RFC 3339.
+    pub timestamp: String,
+    pub method: String,
+    pub serial_number: String,
+    pub tags: Vec<String>,
+    pub detectors: Vec<Detector>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Detector {
+    pub name: String,
+    pub values_per_second: f64,
+    pub values: DVector<f64>,
+    /// In order of retention time.
+    pub peaks: Vec<IntegratedPeak>,
+}
+
+/// The metadata of a [`Run`] without its signal and peaks, see [`read_header`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunHeader {
+    pub id: String,
+    pub name: String,
+    pub timestamp: String,
+    pub method: String,
+    pub serial_number: String,
+    pub tags: Vec<String>,
+    pub detectors: Vec<DetectorHeader>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetectorHeader {
+    pub name: String,
+    pub values_per_second: f64,
+    /// Number of samples.
+    pub len: usize,
+}
+
+/// A peak as integrated by the instrument, times in seconds since injection.
+///
+/// Compounds of the method that were not found are listed with a zero area.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct IntegratedPeak {
+    /// Compound the method assigned the peak to, `None` for unidentified peaks.
+    #[serde(default)]
+    pub label: Option<String>,
+    pub area: f64,
+    pub height: f64,
+    /// Signal to noise ratio, `None` if the source doesn't report one.
+    pub snr: Option<f64>,
+    pub start: f64,
+    pub top: f64,
+    pub end: f64,
+    /// Signal level the area was integrated down to at the start and end of the peak.
+    #[serde(default, rename = "baselinePoints")]
+    pub baseline: Option<Baseline>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Baseline {
+    pub start: f64,
+    pub end: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RunDef<D> {
+    #[serde(rename = "$id")]
+    id: String,
+    #[serde(default)]
+    method_name: String,
+    run_time_stamp: String,
+    #[serde(default)]
+    system_configuration: Option<SystemConfigurationDef>,
+    #[serde(default)]
+    annotations: Option<AnnotationsDef>,
+    detectors: HashMap<String, D>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SystemConfigurationDef {
+    system_info: SystemInfoDef,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SystemInfoDef {
+    system_serial_number: String,
+}
+
+#[derive(Deserialize)]
+struct AnnotationsDef {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// A detector with its samples, deserialized by hand so that the samples go straight into a
+/// buffer of the expected size.
+struct DetectorDef {
+    n_values_per_second: f64,
+    values: Vec<f64>,
+    analysis: Option<AnalysisDef>,
+}
+
+/// A detector without its samples, which are only counted.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DetectorHeaderDef {
+    n_values_per_second: f64,
+    #[serde(default)]
+    values: SampleCount,
+}
+
+#[derive(Deserialize)]
+struct SeriesDef {
+    #[serde(default)]
+    detectors: HashMap<String, DetectorDef>,
+}
+
+#[derive(Deserialize)]
+struct AnalysisDef {
+    #[serde(default)]
+    peaks: Vec<IntegratedPeak>,
+}
+
+/// Upper bound of the buffer reserved up front for a detector's samples, so that a corrupt
+/// `nValuesExpected` can't exhaust memory before the first sample is read. Text runs are never
+/// resampled to more samples.
+const MAX_RESERVED_SAMPLES: usize = 1 << 24;
+
+/// Samples of a detector, read into a buffer reserved for the expected number.
+struct Samples(usize);
+
+/// Number of samples of a detector, counted without keeping them.
+#[derive(Default)]
+struct SampleCount(usize);
+
+impl<'de> DeserializeSeed<'de> for Samples {
+    type Value = Vec<f64>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Samples {
+    type Value = Vec<f64>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("an array of samples")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut values = Vec::with_capacity(self.0.min(MAX_RESERVED_SAMPLES));
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+
+        Ok(values)
+    }
+}
+
+impl<'de> Deserialize<'de> for SampleCount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Counter;
+
+        impl<'de> Visitor<'de> for Counter {
+            type Value = SampleCount;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("an array of samples")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut count = 0;
+                while seq.next_element::<IgnoredAny>()?.is_some() {
+                    count += 1;
+                }
+
+                Ok(SampleCount(count))
+            }
+        }
+
+        deserializer.deserialize_seq(Counter)
+    }
+}
+
+impl<'de> Deserialize<'de> for DetectorDef {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Fields;
+
+        impl<'de> Visitor<'de> for Fields {
+            type Value = DetectorDef;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a detector")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut n_values_per_second = None;
+                let mut expected = 0;
+                let mut values = None;
+                let mut analysis = None;
+
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "nValuesPerSecond" => n_values_per_second = Some(map.next_value()?),
+                        // Fusion writes it before the samples, otherwise the buffer just grows
+                        "nValuesExpected" => {
+                            expected = map.next_value::<Option<usize>>()?.unwrap_or(0)
+                        }
+                        "values" => values = Some(map.next_value_seed(Samples(expected))?),
+                        "analysis" => analysis = map.next_value()?,
+                        _ => {
+                            map.next_value::<IgnoredAny>()?;
+                        }
+                    }
+                }
+
+                Ok(DetectorDef {
+                    n_values_per_second: n_values_per_second
+                        .ok_or_else(|| de::Error::missing_field("nValuesPerSecond"))?,
+                    values: values.ok_or_else(|| de::Error::missing_field("values"))?,
+                    analysis,
+                })
+            }
+        }
+
+        deserializer.deserialize_map(Fields)
+    }
+}
 
-    Ok(DVector::from_vec(data))
+impl<D> RunDef<D> {
+    /// Sample name, tags and instrument serial number, empty if the run doesn't record them.
+    fn labels(&mut self) -> (String, Vec<String>, String) {
+        let (name, tags) = self
+            .annotations
+            .take()
+            .map(|a| (a.name, a.tags))
+            .unwrap_or_default();
+        let serial_number = self
+            .system_configuration
+            .take()
+            .map(|c| c.system_info.system_serial_number)
+            .unwrap_or_default();
+
+        (name, tags, serial_number)
+    }
+}
+
+impl From<RunDef<DetectorDef>> for Run {
+    fn from(mut run: RunDef<DetectorDef>) -> Self {
+        let (name, tags, serial_number) = run.labels();
+
+        let mut detectors = run
+            .detectors
+            .into_iter()
+            .map(|(name, detector)| {
+                let mut peaks = detector.analysis.map(|a| a.peaks).unwrap_or_default();
+                peaks.sort_by(|a, b| a.top.total_cmp(&b.top));
+
+                Detector {
+                    name,
+                    values_per_second: detector.n_values_per_second,
+                    values: DVector::from_vec(detector.values),
+                    peaks,
+                }
+            })
+            .collect::<Vec<_>>();
+        detectors.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Self {
+            id: run.id,
+            name,
+            timestamp: run.run_time_stamp,
+            method: run.method_name,
+            serial_number,
+            tags,
+            detectors,
+        }
+    }
+}
+
+impl From<RunDef<DetectorHeaderDef>> for RunHeader {
+    fn from(mut run: RunDef<DetectorHeaderDef>) -> Self {
+        let (name, tags, serial_number) = run.labels();
+
+        let mut detectors = run
+            .detectors
+            .into_iter()
+            .map(|(name, detector)| DetectorHeader {
+                name,
+                values_per_second: detector.n_values_per_second,
+                len: detector.values.0,
+            })
+            .collect::<Vec<_>>();
+        detectors.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Self {
+            id: run.id,
+            name,
+            timestamp: run.run_time_stamp,
+            method: run.method_name,
+            serial_number,
+            tags,
+            detectors,
+        }
+    }
+}
+
+impl From<&Run> for RunHeader {
+    fn from(run: &Run) -> Self {
+        Self {
+            id: run.id.clone(),
+            name: run.name.clone(),
+            timestamp: run.timestamp.clone(),
+            method: run.method.clone(),
+            serial_number: run.serial_number.clone(),
+            tags: run.tags.clone(),
+            detectors: run
+                .detectors
+                .iter()
+                .map(|d| DetectorHeader {
+                    name: d.name.clone(),
+                    values_per_second: d.values_per_second,
+                    len: d.values.len(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Reads a whole `.fusion-data` run, see [`read_series`] for just the signal.
+pub fn read_run<'a>(path: impl AsRef<Path>) -> Result<Run, ReadError<'a>> {
+    let file = File::open(path).map_err(ReadError::IOError)?;
+    let run: RunDef<DetectorDef> =
+        serde_json::from_reader(BufReader::new(file)).map_err(ReadError::ParseError)?;
+
+    if run.detectors.is_empty() {
+        return Err(ReadError::Other("No detectors."));
+    }
+
+    Ok(run.into())
+}
+
+/// Reads the metadata of a `.fusion-data` run. The samples are skipped as they stream past
+/// instead of being kept, which makes scanning many runs for e.g. an instrument much cheaper
+/// than [`read_run`].
+pub fn read_header<'a>(path: impl AsRef<Path>) -> Result<RunHeader, ReadError<'a>> {
+    let file = File::open(path).map_err(ReadError::IOError)?;
+    let run: RunDef<DetectorHeaderDef> =
+        serde_json::from_reader(BufReader::new(file)).map_err(ReadError::ParseError)?;
+
+    if run.detectors.is_empty() {
+        return Err(ReadError::Other("No detectors."));
+    }
+
+    Ok(run.into())
+}
+
+/// Extensions of the run files [`load_run`] reads.
+pub const RUN_EXTENSIONS: [&str; 6] = ["fusion-data", "cdf", "nc", "csv", "tsv", "txt"];
+
+/// Extensions of the run formats that carry the peaks the instrument integrated. Delimited text
+/// only holds the signal.
+pub const PEAK_TABLE_EXTENSIONS: [&str; 3] = ["fusion-data", "cdf", "nc"];
+
+/// Reads a run in any supported format, picked from the file extension: ANDI netCDF for
+/// `.cdf` and `.nc`, delimited text in `text_format` for `.csv`, `.tsv` and `.txt`, Fusion JSON
+/// otherwise.
+pub fn load_run<'a>(
+    path: impl AsRef<Path>,
+    text_format: &TextFormat,
+) -> Result<Run, ReadError<'a>> {
+    let path = path.as_ref();
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+
+    match extension.as_deref() {
+        Some("cdf" | "nc") => read_andi(path),
+        Some("csv" | "tsv" | "txt") => read_delimited(path, text_format),
+        _ => read_run(path),
+    }
+}
+
+/// Reads the metadata of a run in any format [`load_run`] supports, with [`read_header`] for
+/// Fusion runs. The other formats are small enough to be read whole.
+pub fn load_header<'a>(
+    path: impl AsRef<Path>,
+    text_format: &TextFormat,
+) -> Result<RunHeader, ReadError<'a>> {
+    let path = path.as_ref();
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+
+    match extension.as_deref() {
+        Some("cdf" | "nc" | "csv" | "tsv" | "txt") => {
+            load_run(path, text_format).map(|run| RunHeader::from(&run))
+        }
+        _ => read_header(path),
+    }
+}
+
+impl Run {
+    /// The only detector of the run, the Fusion modules used so far have one each.
+    pub fn detector(&self) -> Result<&Detector, ReadError<'static>> {
+        self.detectors
+            .iter()
+            .at_most_one()
+            .map_err(|_| ReadError::Other("More than one detector."))?
+            .ok_or(ReadError::Other("No detectors."))
+    }
+}
+
+impl Detector {
+    /// Time of a sample in seconds.
+    pub fn time(&self, index: usize) -> f64 {
+        index as f64 / self.values_per_second
+    }
+
+    /// USP tailing factor of `peak`, the width at 5% of its height divided by twice the width of
+    /// its front half. 1 for a symmetric peak, above 1 for a tailing one.
+    ///
+    /// `None` if the signal doesn't drop below 5% within the integration bounds, e.g. for
+    /// co-eluting peaks.
+    pub fn tailing(&self, peak: &IntegratedPeak) -> Option<f64> {
+        let index = |t: f64| (t * self.values_per_second).round() as usize;
+        let (start, top, end) = (index(peak.start), index(peak.top), index(peak.end));
+
+        if start >= top || top >= end || end >= self.values.len() {
+            return None;
+        }
+
+        let (left, right) = match peak.baseline {
+            Some(b) => (b.start, b.end),
+            None => (self.values[start], self.values[end]),
+        };
+        let baseline = |i: usize| left + (right - left) * (i - start) as f64 / (end - start) as f64;
+        let above = |i: usize| self.values[i] - baseline(i);
+
+        let level = 0.05 * above(top);
+        if level <= 0. {
+            return None;
+        }
+
+        let front = (start..top).rev().find(|&i| above(i) <= level)?;
+        let back = (top + 1..=end).find(|&i| above(i) <= level)?;
+
+        Some((back - front) as f64 / (2. * (top - front) as f64))
+    }
 }
 
 #[derive(Debug)]
 pub enum ReadError<'a> {
     ParseError(serde_json::Error),
     IOError(std::io::Error),
+    /// Contents that don't follow the file format, e.g. a truncated netCDF file.
+    Format(String),
     Other(&'a str),
 }
 
+impl Display for ReadError<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReadError::ParseError(e) => write!(f, "Invalid run data: {}", e),
+            ReadError::IOError(e) => write!(f, "{}", e),
+            ReadError::Format(e) => write!(f, "Invalid file: {}", e),
+            ReadError::Other(e) => write!(f, "{}", e),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use crate::io::read_series;
+    use crate::io::{RunHeader, read_header, read_run, read_series};
 
     #[test]
     fn test_fail() {
         assert!(read_series("NOTAFILE").is_err());
+        assert!(read_run("NOTAFILE").is_err());
+        assert!(read_header("NOTAFILE").is_err());
+    }
+
+    #[test]
+    fn test_read_run() {
+        let run = read_run("../../gc-data/R16443 - Jun 08 2025, 09;24.fusion-data").unwrap();
+        let detector = run.detector().unwrap();
+
+        assert_eq!(run.method, "Golden");
+        assert!(!run.serial_number.is_empty());
+        assert_eq!(detector.values.len(), 28000);
+        assert!(detector.peaks.is_sorted_by(|a, b| a.top <= b.top));
+        assert!(detector.peaks.iter().any(|p| p.label.is_none()));
+
+        let tailing = detector
+            .peaks
+            .iter()
+            .filter(|p| p.area > 0.)
+            .filter_map(|p| detector.tailing(p))
+            .collect::<Vec<_>>();
+        assert!(!tailing.is_empty());
+        assert!(tailing.iter().all(|&t| t > 0.5 && t < 5.), "{tailing:?}");
+    }
+
+    #[test]
+    fn test_read_header() {
+        let path = "../../gc-data/R16443 - Jun 08 2025, 09;24.fusion-data";
+        let run = read_run(path).unwrap();
+        let header = read_header(path).unwrap();
+
+        assert_eq!(header, RunHeader::from(&run));
+        assert_eq!(header.serial_number, "70152956");
+        assert_eq!(header.detectors[0].len, 28000);
+
+        let series = read_series(path).unwrap();
+        assert_eq!(series, run.detector().unwrap().values);
+        assert_eq!(series.as_slice().len(), series.data.as_vec().capacity());
     }
 }
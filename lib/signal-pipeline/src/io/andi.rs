@@ -0,0 +1,373 @@
+use std::path::Path;
+
+use nalgebra::DVector;
+
+use super::{Baseline, Detector, IntegratedPeak, ReadError, Run, netcdf::NetCdf};
+
+/// Converts an ANDI time stamp, `YYYYMMDDhhmmss±hhmm`, to RFC 3339. Anything else is kept as
+/// it is.
+fn timestamp(stamp: &str) -> String {
+    let digits = |range: std::ops::Range<usize>| {
+        stamp
+            .get(range)
+            .filter(|s| s.bytes().all(|b| b.is_ascii_digit()))
+    };
+
+    let date_time = (|| {
+        Some(format!(
+            "{}-{}-{}T{}:{}:{}",
+            digits(0..4)?,
+            digits(4..6)?,
+            digits(6..8)?,
+            digits(8..10)?,
+            digits(10..12)?,
+            digits(12..14)?
+        ))
+    })();
+
+    let offset = match stamp.get(14..15) {
+        None | Some("") => Some("Z".to_string()),
+        Some(sign @ ("+" | "-")) => digits(15..17)
+            .zip(digits(17..19))
+            .map(|(h, m)| format!("{}{}:{}", sign, h, m)),
+        Some(_) => None,
+    };
+
+    match date_time.zip(offset) {
+        Some((date_time, offset)) if stamp.len() <= 19 => date_time + &offset,
+        _ => stamp.to_string(),
+    }
+}
+
+/// First value of a scalar variable.
+fn scalar(cdf: &NetCdf, name: &str) -> Option<f64> {
+    cdf.numbers(name).ok()?.first().copied()
+}
+
+/// Seconds per unit of the peak table times, which ANDI allows in minutes.
+fn retention_unit(cdf: &NetCdf) -> f64 {
+    match cdf.text_attribute("retention_unit") {
+        Some(unit) if unit.to_ascii_lowercase().starts_with("min") => 60.,
+        _ => 1.,
+    }
+}
+
+fn peaks(cdf: &NetCdf) -> Result<Vec<IntegratedPeak>, String> {
+    if !cdf.has_variable("peak_retention_time") {
+        return Ok(vec![]);
+    }
+
+    let unit = retention_unit(cdf);
+    let times = |name: &str| -> Result<Vec<f64>, String> {
+        Ok(cdf.numbers(name)?.into_iter().map(|t| t * unit).collect())
+    };
+    let optional = |name: &str| cdf.numbers(name).ok();
+
+    let tops = times("peak_retention_time")?;
+    let starts = times("peak_start_time")?;
+    let ends = times("peak_end_time")?;
+    let areas = cdf.numbers("peak_area")?;
+    let heights = optional("peak_height");
+    let names = cdf.strings("peak_name").ok();
+    let baseline_starts = optional("baseline_start_value");
+    let baseline_ends = optional("baseline_stop_value");
+
+    let at = |values: &Option<Vec<f64>>, i: usize| values.as_ref().and_then(|v| v.get(i).copied());
+
+    let mut peaks = (0..tops.len())
+        .map(|i| {
+            Ok(IntegratedPeak {
+                label: names
+                    .as_ref()
+                    .and_then(|names| names.get(i))
+                    .filter(|name| !name.is_empty())
+                    .cloned(),
+                area: *areas.get(i).ok_or("Fewer peak areas than peaks.")?,
+                height: at(&heights, i).unwrap_or(0.),
+                snr: None,
+                start: *starts.get(i).ok_or("Fewer peak starts than peaks.")?,
+                top: tops[i],
+                end: *ends.get(i).ok_or("Fewer peak ends than peaks.")?,
+                baseline: at(&baseline_starts, i)
+                    .zip(at(&baseline_ends, i))
+                    .map(|(start, end)| Baseline { start, end }),
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    peaks.sort_by(|a, b| a.top.total_cmp(&b.top));
+
+    Ok(peaks)
+}
+
+/// Reads an ANDI/AIA chromatography file (ASTM E1947), the netCDF export of most Agilent,
+/// Shimadzu and Thermo data systems, into a [`Run`] with a single detector.
+///
+/// Our detectors start sampling at injection, so a signal recorded after a delay is padded
+/// with its first value up to the delay.
+pub fn read_andi<'a>(path: impl AsRef<Path>) -> Result<Run, ReadError<'a>> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path).map_err(ReadError::IOError)?;
+
+    parse_andi(&bytes, path).map_err(ReadError::Format)
+}
+
+fn parse_andi(bytes: &[u8], path: &Path) -> Result<Run, String> {
+    let cdf = NetCdf::parse(bytes)?;
+
+    let interval = scalar(&cdf, "actual_sampling_interval")
+        .filter(|&i| i > 0.)
+        .ok_or("No actual_sampling_interval.")?;
+    let values_per_second = 1. / interval;
+
+    // the E1947 name is ordinate_values, some exporters write ordinal_values
+    let signal = ["ordinate_values", "ordinal_values"]
+        .iter()
+        .find(|name| cdf.has_variable(name))
+        .ok_or(String::from("No ordinate_values."))
+        .and_then(|name| cdf.numbers(name))?;
+
+    // raw data timing is in seconds whatever the peak table's retention_unit
+    let delay = scalar(&cdf, "actual_delay_time").unwrap_or(0.);
+    let skipped = (delay * values_per_second).round() as i64;
+    let values = match (skipped, signal.first()) {
+        (0.., Some(&first)) => std::iter::repeat_n(first, skipped as usize)
+            .chain(signal.iter().copied())
+            .collect(),
+        _ => signal
+            .into_iter()
+            .skip(skipped.unsigned_abs() as usize)
+            .collect(),
+    };
+
+    let text = |name: &str| cdf.text_attribute(name).unwrap_or_default();
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let serial_number = cdf
+        .strings("instrument_serial_no")
+        .ok()
+        .and_then(|s| s.into_iter().find(|s| !s.is_empty()))
+        .unwrap_or_default();
+
+    Ok(Run {
+        id: cdf.text_attribute("sample_id").unwrap_or(stem),
+        name: text("sample_name"),
+        timestamp: timestamp(&text("injection_date_time_stamp")),
+        method: text("detection_method_name"),
+        serial_number,
+        // the lab the file came from
+        tags: cdf.text_attribute("dataset_origin").into_iter().collect(),
+        detectors: vec![Detector {
+            name: cdf.text_attribute("detector_name").unwrap_or("andi".into()),
+            values_per_second,
+            values: DVector::from_vec(values),
+            peaks: peaks(&cdf)?,
+        }],
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Values of a variable written by [`cdf`].
+    enum Data {
+        Doubles(Vec<f64>),
+        Floats(Vec<f32>),
+        Chars(Vec<u8>),
+    }
+
+    fn name(out: &mut Vec<u8>, name: &str) {
+        out.extend((name.len() as u32).to_be_bytes());
+        out.extend(name.as_bytes());
+        out.resize(out.len().next_multiple_of(4), 0);
+    }
+
+    /// Writes a minimal CDF-1 file.
+    fn cdf(
+        dims: &[(&str, u32)],
+        attributes: &[(&str, &str)],
+        variables: &[(&str, &[u32], Data)],
+    ) -> Vec<u8> {
+        let encode = |data: &Data| -> (u32, Vec<u8>) {
+            let (nc_type, mut bytes) = match data {
+                Data::Doubles(v) => (6, v.iter().flat_map(|x| x.to_be_bytes()).collect()),
+                Data::Floats(v) => (5, v.iter().flat_map(|x| x.to_be_bytes()).collect()),
+                Data::Chars(v) => (2, v.clone()),
+            };
+            bytes.resize(bytes.len().next_multiple_of(4), 0);
+            (nc_type, bytes)
+        };
+
+        let mut header = b"CDF\x01".to_vec();
+        header.extend(0u32.to_be_bytes());
+
+        header.extend(0x0Au32.to_be_bytes());
+        header.extend((dims.len() as u32).to_be_bytes());
+        for (dim, len) in dims {
+            name(&mut header, dim);
+            header.extend(len.to_be_bytes());
+        }
+
+        header.extend(0x0Cu32.to_be_bytes());
+        header.extend((attributes.len() as u32).to_be_bytes());
+        for (attribute, value) in attributes {
+            name(&mut header, attribute);
+            header.extend(2u32.to_be_bytes());
+            header.extend((value.len() as u32).to_be_bytes());
+            header.extend(value.as_bytes());
+            header.resize(header.len().next_multiple_of(4), 0);
+        }
+
+        // every variable entry has a fixed size apart from the name and dimensions, so the
+        // header length is known before the offsets are
+        let header_len = header.len()
+            + 8
+            + variables
+                .iter()
+                .map(|(var, ids, _)| {
+                    4 + var.len().next_multiple_of(4) + 4 + 4 * ids.len() + 8 + 4 + 4 + 4
+                })
+                .sum::<usize>();
+
+        let mut data = vec![];
+        header.extend(0x0Bu32.to_be_bytes());
+        header.extend((variables.len() as u32).to_be_bytes());
+        for (var, ids, values) in variables {
+            let (nc_type, bytes) = encode(values);
+
+            name(&mut header, var);
+            header.extend((ids.len() as u32).to_be_bytes());
+            for id in *ids {
+                header.extend(id.to_be_bytes());
+            }
+            header.extend([0; 8]);
+            header.extend(nc_type.to_be_bytes());
+            header.extend((bytes.len() as u32).to_be_bytes());
+            header.extend(((header_len + data.len()) as u32).to_be_bytes());
+            data.extend(bytes);
+        }
+
+        assert_eq!(header.len(), header_len);
+        header.extend(data);
+        header
+    }
+
+    fn names(names: &[&str]) -> Data {
+        Data::Chars(
+            names
+                .iter()
+                .flat_map(|n| {
+                    let mut padded = n.as_bytes().to_vec();
+                    padded.resize(32, 0);
+                    padded
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn andi_file() {
+        let signal = (0..400)
+            .map(|i| 10. + 1000. * (-((i as f32 - 300.) / 10.).powi(2)).exp())
+            .collect::<Vec<_>>();
+
+        let bytes = cdf(
+            &[
+                ("point_number", 400),
+                ("peak_number", 2),
+                ("_32_byte_string", 32),
+            ],
+            &[
+                ("sample_name", "Cylinder 7"),
+                ("injection_date_time_stamp", "20250608092400-0500"),
+                ("detector_name", "TCD"),
+                ("detection_method_name", "Refrigerants"),
+                ("retention_unit", "Minutes"),
+            ],
+            &[
+                ("actual_sampling_interval", &[], Data::Doubles(vec![0.05])),
+                ("actual_delay_time", &[], Data::Doubles(vec![0.5])),
+                ("ordinate_values", &[0], Data::Floats(signal)),
+                (
+                    "peak_retention_time",
+                    &[1],
+                    Data::Floats(vec![15.5 / 60., 10.5 / 60.]),
+                ),
+                (
+                    "peak_start_time",
+                    &[1],
+                    Data::Floats(vec![14.5 / 60., 10. / 60.]),
+                ),
+                (
+                    "peak_end_time",
+                    &[1],
+                    Data::Floats(vec![16.5 / 60., 11. / 60.]),
+                ),
+                ("peak_area", &[1], Data::Floats(vec![17725., 0.])),
+                ("peak_height", &[1], Data::Floats(vec![1000., 0.])),
+                ("baseline_start_value", &[1], Data::Floats(vec![10., 0.])),
+                ("baseline_stop_value", &[1], Data::Floats(vec![10., 0.])),
+                ("peak_name", &[1, 2], names(&["R-22", ""])),
+            ],
+        );
+
+        let run = parse_andi(&bytes, Path::new("cylinder-7.cdf")).unwrap();
+        let detector = run.detector().unwrap();
+
+        assert_eq!(run.id, "cylinder-7");
+        assert_eq!(run.name, "Cylinder 7");
+        assert_eq!(run.timestamp, "2025-06-08T09:24:00-05:00");
+        assert_eq!(run.method, "Refrigerants");
+        assert_eq!(detector.name, "TCD");
+        assert_eq!(detector.values_per_second, 20.);
+        // 0.5 s of delay padded in front, in seconds although the peaks are in minutes
+        assert_eq!(detector.values.len(), 410);
+        assert_eq!(detector.values.argmax().0, 310);
+
+        assert_eq!(detector.peaks.len(), 2);
+        assert_eq!(detector.peaks[0].label, None);
+        let peak = &detector.peaks[1];
+        assert_eq!(peak.label.as_deref(), Some("R-22"));
+        assert!((peak.top - 15.5).abs() < 1e-4);
+        assert!((peak.end - 16.5).abs() < 1e-4);
+        assert_eq!(
+            peak.baseline,
+            Some(Baseline {
+                start: 10.,
+                end: 10.
+            })
+        );
+        assert_eq!(peak.snr, None);
+        assert!((detector.time(310) - peak.top).abs() < 1e-4);
+
+        assert!(parse_andi(b"CDF\x01", Path::new("short.cdf")).is_err());
+        assert!(parse_andi(&bytes[..bytes.len() - 100], Path::new("cut.cdf")).is_err());
+
+        // dimensions whose product doesn't fit in memory, let alone the file
+        let huge = cdf(
+            &[("a", u32::MAX), ("b", u32::MAX), ("c", u32::MAX)],
+            &[],
+            &[
+                ("actual_sampling_interval", &[], Data::Doubles(vec![0.05])),
+                ("ordinate_values", &[0, 1, 2], Data::Floats(vec![1.])),
+            ],
+        );
+        assert_eq!(
+            parse_andi(&huge, Path::new("huge.cdf")).unwrap_err(),
+            "Malformed netCDF header."
+        );
+    }
+
+    #[test]
+    fn andi_timestamps() {
+        assert_eq!(
+            timestamp("20240604115251+0200"),
+            "2024-06-04T11:52:51+02:00"
+        );
+        assert_eq!(timestamp("20240604115251"), "2024-06-04T11:52:51Z");
+        assert_eq!(timestamp("June 4th"), "June 4th");
+    }
+}
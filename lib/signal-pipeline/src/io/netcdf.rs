@@ -0,0 +1,334 @@
+use std::collections::HashMap;
+
+const ABSENT: u32 = 0;
+const NC_DIMENSION: u32 = 0x0A;
+const NC_VARIABLE: u32 = 0x0B;
+const NC_ATTRIBUTE: u32 = 0x0C;
+const STREAMING: u32 = 0xFFFF_FFFF;
+
+/// Values of an attribute or variable, numbers of any width widened to `f64`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Values {
+    Text(Vec<u8>),
+    Numbers(Vec<f64>),
+}
+
+#[derive(Debug, Clone)]
+struct Variable {
+    /// Length of every dimension, the record dimension counted as the number of records.
+    shape: Vec<usize>,
+    nc_type: u32,
+    /// Bytes of one record, or of the whole variable if it is not a record variable.
+    size: usize,
+    begin: usize,
+    is_record: bool,
+}
+
+/// An in-memory file in the classic netCDF-3 format, CDF-1 or the 64 bit offset CDF-2, as used
+/// by ANDI chromatography files. Everything is big endian and padded to four bytes.
+#[derive(Debug)]
+pub struct NetCdf<'b> {
+    bytes: &'b [u8],
+    attributes: HashMap<String, Values>,
+    variables: HashMap<String, Variable>,
+    record_size: usize,
+}
+
+struct Cursor<'b> {
+    bytes: &'b [u8],
+    pos: usize,
+}
+
+impl<'b> Cursor<'b> {
+    fn take(&mut self, n: usize) -> Result<&'b [u8], String> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or("Unexpected end of the netCDF header.")?;
+        let taken = &self.bytes[self.pos..end];
+        self.pos = end;
+
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_be_bytes(
+            self.take(4)?.try_into().expect("took 4 bytes"),
+        ))
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_be_bytes(
+            self.take(8)?.try_into().expect("took 8 bytes"),
+        ))
+    }
+
+    fn size(&mut self) -> Result<usize, String> {
+        Ok(self.u32()? as usize)
+    }
+
+    fn padded(&mut self, n: usize) -> Result<&'b [u8], String> {
+        let taken = self.take(n)?;
+        self.take(padding(n))?;
+
+        Ok(taken)
+    }
+
+    fn name(&mut self) -> Result<String, String> {
+        let len = self.size()?;
+
+        String::from_utf8(self.padded(len)?.to_vec()).map_err(|_| "Invalid netCDF name.".into())
+    }
+
+    /// A list of `tag` elements, which is either absent or has the tag followed by its length.
+    fn list(&mut self, tag: u32) -> Result<usize, String> {
+        match (self.u32()?, self.size()?) {
+            (ABSENT, 0) => Ok(0),
+            (t, n) if t == tag => Ok(n),
+            _ => Err("Malformed netCDF header.".into()),
+        }
+    }
+
+    fn attributes(&mut self) -> Result<HashMap<String, Values>, String> {
+        (0..self.list(NC_ATTRIBUTE)?)
+            .map(|_| {
+                let name = self.name()?;
+                let nc_type = self.u32()?;
+                let n = self.size()?;
+                let len = n
+                    .checked_mul(type_size(nc_type)?)
+                    .ok_or("Malformed netCDF header.")?;
+                let bytes = self.padded(len)?;
+
+                Ok((name, decode(nc_type, bytes)?))
+            })
+            .collect()
+    }
+}
+
+fn padding(n: usize) -> usize {
+    (4 - n % 4) % 4
+}
+
+/// Bytes taken by values of `shape`, failing instead of overflowing on made up dimensions.
+fn byte_len(shape: &[usize], element: usize) -> Result<usize, String> {
+    shape
+        .iter()
+        .try_fold(element, |len, &dim| len.checked_mul(dim))
+        .ok_or("Malformed netCDF header.".into())
+}
+
+fn type_size(nc_type: u32) -> Result<usize, String> {
+    match nc_type {
+        1 | 2 => Ok(1),
+        3 => Ok(2),
+        4 | 5 => Ok(4),
+        6 => Ok(8),
+        t => Err(format!("Unknown netCDF type {}.", t)),
+    }
+}
+
+fn decode(nc_type: u32, bytes: &[u8]) -> Result<Values, String> {
+    let numbers = |size: usize, f: fn(&[u8]) -> f64| {
+        Values::Numbers(bytes.chunks_exact(size).map(f).collect())
+    };
+
+    Ok(match nc_type {
+        1 => numbers(1, |b| b[0] as i8 as f64),
+        2 => Values::Text(bytes.to_vec()),
+        3 => numbers(2, |b| i16::from_be_bytes([b[0], b[1]]) as f64),
+        4 => numbers(4, |b| {
+            i32::from_be_bytes(b.try_into().expect("chunks of 4")) as f64
+        }),
+        5 => numbers(4, |b| {
+            f32::from_be_bytes(b.try_into().expect("chunks of 4")) as f64
+        }),
+        6 => numbers(8, |b| {
+            f64::from_be_bytes(b.try_into().expect("chunks of 8"))
+        }),
+        t => return Err(format!("Unknown netCDF type {}.", t)),
+    })
+}
+
+/// Text with the trailing NULs and blanks netCDF pads fixed width strings with removed.
+fn text(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes)
+        .trim_end_matches(['\0', ' '])
+        .to_string()
+}
+
+impl<'b> NetCdf<'b> {
+    pub fn parse(bytes: &'b [u8]) -> Result<Self, String> {
+        let mut cursor = Cursor { bytes, pos: 0 };
+
+        let offset64 = match cursor.take(4) {
+            Ok(b"CDF\x01") => false,
+            Ok(b"CDF\x02") => true,
+            _ => return Err("Not a classic netCDF file.".into()),
+        };
+
+        let records = match cursor.u32()? {
+            STREAMING => None,
+            n => Some(n as usize),
+        };
+
+        let dimensions = (0..cursor.list(NC_DIMENSION)?)
+            .map(|_| {
+                cursor.name()?;
+                cursor.size()
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let attributes = cursor.attributes()?;
+
+        let mut variables = HashMap::new();
+
+        for _ in 0..cursor.list(NC_VARIABLE)? {
+            let name = cursor.name()?;
+            let dims = (0..cursor.size()?)
+                .map(|_| {
+                    let id = cursor.size()?;
+                    dimensions
+                        .get(id)
+                        .map(|&len| (id, len))
+                        .ok_or(format!("Unknown netCDF dimension {}.", id))
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            cursor.attributes()?;
+            let nc_type = cursor.u32()?;
+            type_size(nc_type)?;
+            let size = cursor.size()?;
+            let begin = if offset64 {
+                cursor.u64()? as usize
+            } else {
+                cursor.size()?
+            };
+
+            let is_record = dims.first().is_some_and(|&(_, len)| len == 0);
+
+            variables.insert(
+                name,
+                Variable {
+                    shape: dims.iter().map(|&(_, len)| len).collect(),
+                    nc_type,
+                    size,
+                    begin,
+                    is_record,
+                },
+            );
+        }
+
+        let record_variables = variables
+            .values()
+            .filter(|v| v.is_record)
+            .collect::<Vec<_>>();
+
+        // records are padded to four bytes, unless there is only one record variable
+        let record_size = match record_variables[..] {
+            [only] => byte_len(&only.shape[1..], type_size(only.nc_type)?)?,
+            _ => record_variables
+                .iter()
+                .try_fold(0usize, |sum, v| sum.checked_add(v.size))
+                .ok_or("Malformed netCDF header.")?,
+        };
+
+        // a streamed file doesn't know its length up front, it has as many records as fit
+        let records = match records {
+            Some(n) => n,
+            None => variables
+                .values()
+                .filter(|v| v.is_record)
+                .map(|v| v.begin)
+                .min()
+                .filter(|_| record_size > 0)
+                .map_or(0, |start| bytes.len().saturating_sub(start) / record_size),
+        };
+
+        for variable in variables.values_mut().filter(|v| v.is_record) {
+            variable.shape[0] = records;
+        }
+
+        Ok(Self {
+            bytes,
+            attributes,
+            variables,
+            record_size,
+        })
+    }
+
+    /// A global attribute.
+    pub fn attribute(&self, name: &str) -> Option<&Values> {
+        self.attributes.get(name)
+    }
+
+    /// A global text attribute, `None` if missing or empty.
+    pub fn text_attribute(&self, name: &str) -> Option<String> {
+        match self.attribute(name)? {
+            Values::Text(bytes) => Some(text(bytes)).filter(|t| !t.is_empty()),
+            Values::Numbers(_) => None,
+        }
+    }
+
+    pub fn has_variable(&self, name: &str) -> bool {
+        self.variables.contains_key(name)
+    }
+
+    /// All values of a variable, flattened in row major order.
+    pub fn variable(&self, name: &str) -> Result<Values, String> {
+        let variable = self
+            .variables
+            .get(name)
+            .ok_or(format!("No variable {}.", name))?;
+        let element = type_size(variable.nc_type)?;
+
+        let bytes = if variable.is_record {
+            let per_record = byte_len(&variable.shape[1..], element)?;
+
+            (0..variable.shape[0])
+                .map(|r| {
+                    let start = r
+                        .checked_mul(self.record_size)
+                        .and_then(|offset| offset.checked_add(variable.begin))
+                        .ok_or("Malformed netCDF header.")?;
+                    self.slice(start, per_record)
+                })
+                .collect::<Result<Vec<_>, String>>()?
+                .concat()
+        } else {
+            let len = byte_len(&variable.shape, element)?;
+            self.slice(variable.begin, len)?.to_vec()
+        };
+
+        decode(variable.nc_type, &bytes)
+    }
+
+    pub fn numbers(&self, name: &str) -> Result<Vec<f64>, String> {
+        match self.variable(name)? {
+            Values::Numbers(numbers) => Ok(numbers),
+            Values::Text(_) => Err(format!("Variable {} is text, not numbers.", name)),
+        }
+    }
+
+    /// A character variable as one string per row of its last dimension.
+    pub fn strings(&self, name: &str) -> Result<Vec<String>, String> {
+        let width = self
+            .variables
+            .get(name)
+            .and_then(|v| v.shape.last().copied())
+            .unwrap_or(0);
+
+        match self.variable(name)? {
+            Values::Text(bytes) if width > 0 => Ok(bytes.chunks(width).map(text).collect()),
+            Values::Text(_) => Ok(vec![]),
+            Values::Numbers(_) => Err(format!("Variable {} is numbers, not text.", name)),
+        }
+    }
+
+    fn slice(&self, start: usize, len: usize) -> Result<&'b [u8], String> {
+        start
+            .checked_add(len)
+            .and_then(|end| self.bytes.get(start..end))
+            .ok_or("netCDF data ends early.".into())
+    }
+}
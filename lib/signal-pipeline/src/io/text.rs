@@ -0,0 +1,394 @@
+use std::{path::Path, str::FromStr};
+
+use nalgebra::DVector;
+use serde::Deserialize;
+
+use super::{Detector, MAX_RESERVED_SAMPLES, ReadError, Run};
+
+/// A column of a delimited text file, by position from 0 or by its header.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum Column {
+    Index(usize),
+    Name(String),
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeUnit {
+    Milliseconds,
+    #[default]
+    Seconds,
+    Minutes,
+}
+
+/// How to read a delimited text chromatogram, see [`read_delimited`].
+///
+/// The defaults read `time,signal` files in seconds, with or without a header line.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct TextFormat {
+    /// Field separator, guessed for every line if `None`: tab, comma, semicolon or any
+    /// whitespace, whichever comes first in that order.
+    pub delimiter: Option<char>,
+    /// Lines to drop before the data, e.g. an instrument's preamble. A header line after them
+    /// is recognized on its own.
+    pub skip_lines: usize,
+    pub time_column: Column,
+    pub signal_column: Column,
+    pub time_unit: TimeUnit,
+    /// Decimal separator, `,` for most European exports.
+    pub decimal: char,
+    /// Rate the signal is resampled to, the median rate of the file if `None`.
+    pub values_per_second: Option<f64>,
+}
+
+/// Data lines of a delimited text file.
+struct Samples {
+    /// `(time in seconds, signal)`, in the order of the file.
+    points: Vec<(f64, f64)>,
+    /// Header of the signal column, if the file has one.
+    signal_name: Option<String>,
+}
+
+impl Default for TextFormat {
+    fn default() -> Self {
+        Self {
+            delimiter: None,
+            skip_lines: 0,
+            time_column: Column::Index(0),
+            signal_column: Column::Index(1),
+            time_unit: TimeUnit::Seconds,
+            decimal: '.',
+            values_per_second: None,
+        }
+    }
+}
+
+impl FromStr for Column {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.trim().parse() {
+            Ok(index) => Column::Index(index),
+            Err(_) => Column::Name(s.trim().to_string()),
+        })
+    }
+}
+
+impl FromStr for TimeUnit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "ms" | "milliseconds" => Ok(TimeUnit::Milliseconds),
+            "s" | "seconds" => Ok(TimeUnit::Seconds),
+            "min" | "minutes" => Ok(TimeUnit::Minutes),
+            other => Err(format!("Unknown time unit {}.", other)),
+        }
+    }
+}
+
+impl TimeUnit {
+    fn seconds(self) -> f64 {
+        match self {
+            TimeUnit::Milliseconds => 0.001,
+            TimeUnit::Seconds => 1.,
+            TimeUnit::Minutes => 60.,
+        }
+    }
+}
+
+impl TextFormat {
+    pub fn with_delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = Some(delimiter);
+        self
+    }
+
+    pub fn with_skip_lines(mut self, lines: usize) -> Self {
+        self.skip_lines = lines;
+        self
+    }
+
+    pub fn with_columns(mut self, time: Column, signal: Column) -> Self {
+        self.time_column = time;
+        self.signal_column = signal;
+        self
+    }
+
+    pub fn with_time_unit(mut self, unit: TimeUnit) -> Self {
+        self.time_unit = unit;
+        self
+    }
+
+    pub fn with_decimal(mut self, decimal: char) -> Self {
+        self.decimal = decimal;
+        self
+    }
+
+    pub fn with_values_per_second(mut self, values_per_second: f64) -> Self {
+        self.values_per_second = Some(values_per_second);
+        self
+    }
+
+    fn split<'l>(&self, line: &'l str) -> Vec<&'l str> {
+        let delimiter = self.delimiter.or_else(|| {
+            ['\t', ',', ';']
+                .into_iter()
+                .filter(|&d| d != self.decimal)
+                .find(|&d| line.contains(d))
+        });
+
+        match delimiter {
+            Some(d) => line.split(d).map(str::trim).collect(),
+            None => line.split_whitespace().collect(),
+        }
+    }
+
+    fn number(&self, field: &str) -> Option<f64> {
+        let field = field.trim_matches('"');
+
+        if self.decimal == '.' {
+            field.parse().ok()
+        } else {
+            field.replace(self.decimal, ".").parse().ok()
+        }
+    }
+
+    fn index(&self, column: &Column, header: Option<&[&str]>) -> Result<usize, String> {
+        match column {
+            Column::Index(index) => Ok(*index),
+            Column::Name(name) => header
+                .ok_or(format!("No header line to find column {} in.", name))?
+                .iter()
+                .position(|h| h.trim_matches('"').eq_ignore_ascii_case(name))
+                .ok_or(format!("No column {}.", name)),
+        }
+    }
+
+    fn samples(&self, text: &str) -> Result<Samples, String> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .skip(self.skip_lines)
+            .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+            .peekable();
+
+        let header = lines
+            .next_if(|(_, line)| {
+                self.split(line)
+                    .iter()
+                    .any(|field| self.number(field).is_none())
+            })
+            .map(|(_, line)| self.split(line));
+
+        let time = self.index(&self.time_column, header.as_deref())?;
+        let signal = self.index(&self.signal_column, header.as_deref())?;
+
+        let samples = lines
+            .map(|(number, line)| {
+                let fields = self.split(line);
+                let field = |column: usize| {
+                    fields
+                        .get(column)
+                        .and_then(|f| self.number(f))
+                        .ok_or(format!(
+                            "Line {}: no number in column {}.",
+                            number + 1,
+                            column
+                        ))
+                };
+
+                Ok((field(time)? * self.time_unit.seconds(), field(signal)?))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(Samples {
+            points: samples,
+            signal_name: header
+                .as_ref()
+                .and_then(|h| h.get(signal))
+                .map(|name| name.trim_matches('"').to_string()),
+        })
+    }
+}
+
+/// Resamples `samples` onto a uniform grid from injection at 0 s, interpolating linearly.
+/// Samples at the same time are averaged, the signal before the first sample is the first
+/// value. A grid of more than [`MAX_RESERVED_SAMPLES`] values is an error.
+fn resample(
+    mut samples: Vec<(f64, f64)>,
+    values_per_second: Option<f64>,
+) -> Result<(Vec<f64>, f64), String> {
+    samples.retain(|(t, v)| t.is_finite() && v.is_finite());
+    samples.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut merged: Vec<(f64, f64, usize)> = vec![];
+    for (t, v) in samples {
+        match merged.last_mut() {
+            Some((last, sum, n)) if *last == t => {
+                *sum += v;
+                *n += 1;
+            }
+            _ => merged.push((t, v, 1)),
+        }
+    }
+    let samples = merged
+        .into_iter()
+        .map(|(t, sum, n)| (t, sum / n as f64))
+        .collect::<Vec<_>>();
+
+    if samples.len() < 2 {
+        return Err("Fewer than two samples.".into());
+    }
+
+    let values_per_second = match values_per_second {
+        Some(rate) if rate > 0. => rate,
+        Some(_) => return Err("The sampling rate must be positive.".into()),
+        None => {
+            let mut steps = samples
+                .windows(2)
+                .map(|w| w[1].0 - w[0].0)
+                .collect::<Vec<_>>();
+            steps.sort_by(f64::total_cmp);
+
+            1. / steps[steps.len() / 2]
+        }
+    };
+
+    let end = samples[samples.len() - 1].0;
+    if end <= 0. {
+        return Err("No samples after injection.".into());
+    }
+
+    let last = (end * values_per_second).round();
+    if last >= MAX_RESERVED_SAMPLES as f64 {
+        return Err(format!(
+            "Resampling {end} s at {values_per_second} values per second takes too many samples."
+        ));
+    }
+
+    let mut next = 0;
+    let values = (0..=last as usize)
+        .map(|i| {
+            let t = i as f64 / values_per_second;
+
+            while next < samples.len() && samples[next].0 < t {
+                next += 1;
+            }
+
+            match next {
+                0 => samples[0].1,
+                n if n == samples.len() => samples[n - 1].1,
+                n => {
+                    let (t0, v0) = samples[n - 1];
+                    let (t1, v1) = samples[n];
+
+                    v0 + (v1 - v0) * (t - t0) / (t1 - t0)
+                }
+            }
+        })
+        .collect();
+
+    Ok((values, values_per_second))
+}
+
+/// Reads a delimited text chromatogram, e.g. a `time,signal` CSV, into a [`Run`] without
+/// integrated peaks. Uneven time steps are resampled to a uniform grid.
+pub fn read_delimited<'a>(
+    path: impl AsRef<Path>,
+    format: &TextFormat,
+) -> Result<Run, ReadError<'a>> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path).map_err(ReadError::IOError)?;
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    parse_delimited(&text, format, &stem).map_err(ReadError::Format)
+}
+
+fn parse_delimited(text: &str, format: &TextFormat, name: &str) -> Result<Run, String> {
+    let samples = format.samples(text)?;
+    let (values, values_per_second) = resample(samples.points, format.values_per_second)?;
+
+    Ok(Run {
+        id: name.into(),
+        name: name.into(),
+        timestamp: String::new(),
+        method: String::new(),
+        serial_number: String::new(),
+        tags: vec![],
+        detectors: vec![Detector {
+            name: samples.signal_name.unwrap_or("signal".into()),
+            values_per_second,
+            values: DVector::from_vec(values),
+            peaks: vec![],
+        }],
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn delimited_text() {
+        let csv = "time,tcd\n0,1\n0.5,2\n1.0,3\n1.5,4\n2.0,5\n";
+        let run = parse_delimited(csv, &TextFormat::default(), "simple").unwrap();
+        let detector = run.detector().unwrap();
+
+        assert_eq!(detector.name, "tcd");
+        assert_eq!(detector.values_per_second, 2.);
+        assert_eq!(detector.values.as_slice(), [1., 2., 3., 4., 5.]);
+
+        // an older instrument: preamble, tabs, decimal commas, minutes, named columns in a
+        // different order and uneven steps starting after injection
+        let export = "Instrument GC-2\nOperator: lab\n\
+                      Signal (mV)\tTime (min)\n\
+                      2,0\t0,01\n\
+                      4,0\t0,02\n\
+                      8,0\t0,04\n\
+                      8,0\t0,04\n";
+        let format = TextFormat::default()
+            .with_skip_lines(2)
+            .with_columns(
+                "Time (min)".parse().unwrap(),
+                "Signal (mV)".parse().unwrap(),
+            )
+            .with_time_unit(TimeUnit::Minutes)
+            .with_decimal(',')
+            .with_values_per_second(5.);
+        let run = parse_delimited(export, &format, "old").unwrap();
+        let detector = run.detector().unwrap();
+
+        assert_eq!(detector.name, "Signal (mV)");
+        assert_eq!(detector.values_per_second, 5.);
+        // 0.6 s to 2.4 s, padded before the first sample
+        assert_eq!(detector.values.len(), 13);
+        assert_eq!(detector.values[0], 2.);
+        assert!((detector.values[6] - 4.).abs() < 1e-9);
+        assert!((detector.values[9] - 6.).abs() < 1e-9);
+        assert_eq!(detector.values[12], 8.);
+
+        assert!(parse_delimited("t,v\n0,1\n1,x\n", &TextFormat::default(), "bad").is_err());
+        assert!(
+            parse_delimited(
+                "t,v\n0,1\n1,2\n",
+                &TextFormat::default().with_columns(Column::Index(0), "tcd".parse().unwrap()),
+                "missing"
+            )
+            .is_err()
+        );
+        // a stray time stamp far after the run
+        assert!(
+            parse_delimited(
+                "t,v\n0,1\n0.1,2\n0.2,3\n1e12,4\n",
+                &TextFormat::default(),
+                "far"
+            )
+            .is_err()
+        );
+    }
+}
@@ -1,19 +1,22 @@
-use std::path::Path;
+use std::{error::Error, path::Path};
 
 use nalgebra::DVector;
 use nearly::assert_nearly;
 use plotters::{
     chart::ChartBuilder,
-    prelude::{BitMapBackend, IntoDrawingArea},
+    coord::Shift,
+    prelude::{DrawingArea, DrawingBackend},
     series::{DashedLineSeries, LineSeries},
     style::{BLUE, IntoFont, RED, RGBColor, WHITE, full_palette::ORANGE},
 };
 
-use crate::peak_detection::Peak;
+use crate::{peak_detection::Peak, plot::Figure};
 
 pub mod io;
 pub mod peak_detection;
+pub mod plot;
 pub mod preprocess;
+pub mod processing;
 
 pub fn nearly_eq(a: &DVector<f64>, b: &DVector<f64>) {
     a.iter()
@@ -21,150 +24,114 @@ pub fn nearly_eq(a: &DVector<f64>, b: &DVector<f64>) {
         .for_each(|(&a, &b)| assert_nearly!(a == b));
 }
 
-pub fn simple_graph_vecs_with_peaks(
-    path: impl AsRef<Path>,
-    data: &[(&DVector<f64>, &RGBColor)],
-    peaks: &[Peak],
-) {
-    let min = data
-        .iter()
-        .map(|v| v.0.min())
-        .reduce(|a, b| a.min(b))
-        .unwrap();
-
-    let max = data
-        .iter()
-        .map(|v| v.0.max())
-        .reduce(|a, b| a.max(b))
-        .unwrap();
-
-    let len = data.iter().map(|v| v.0.len()).max().unwrap();
+/// Signals against sample index with detected peaks, for debugging the peak detection.
+struct SignalPlot<'a> {
+    title: String,
+    size: (u32, u32),
+    data: &'a [(&'a DVector<f64>, &'a RGBColor)],
+    peaks: &'a [Peak],
+}
 
-    let path = path.as_ref();
-    let root = BitMapBackend::new(path, (3840, 2160)).into_drawing_area();
-    root.fill(&WHITE).unwrap();
-    let mut chart = ChartBuilder::on(&root)
-        .caption(
-            path.components()
-                .last()
-                .unwrap()
-                .as_os_str()
-                .to_string_lossy(),
-            ("sans-serif", 50).into_font(),
-        )
-        .margin(10)
-        .x_label_area_size(30)
-        .y_label_area_size(30)
-        .build_cartesian_2d(0..len, min..max)
-        .unwrap();
-
-    chart.configure_mesh().draw().unwrap();
-
-    for (vec, color) in data {
-        chart
-            .draw_series(LineSeries::new(vec.iter().copied().enumerate(), color))
-            .unwrap();
+impl Figure for SignalPlot<'_> {
+    fn size(&self) -> (u32, u32) {
+        self.size
     }
 
-    for peak in peaks {
-        chart
-            .draw_series(LineSeries::new(
-                [(peak.pos as usize, 0.0), (peak.pos as usize, peak.height)].into_iter(),
+    fn draw<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>) -> Result<(), Box<dyn Error>>
+    where
+        DB::ErrorType: 'static,
+    {
+        let min = self
+            .data
+            .iter()
+            .map(|v| v.0.min())
+            .reduce(f64::min)
+            .ok_or("Nothing to plot.")?;
+        let max = self.data.iter().map(|v| v.0.max()).fold(min, f64::max);
+        let len = self.data.iter().map(|v| v.0.len()).max().unwrap_or(0);
+
+        root.fill(&WHITE)?;
+        let mut chart = ChartBuilder::on(root)
+            .caption(&self.title, ("sans-serif", 50).into_font())
+            .margin(10)
+            .x_label_area_size(30)
+            .y_label_area_size(30)
+            .build_cartesian_2d(0..len, min..max)?;
+
+        chart.configure_mesh().draw()?;
+
+        for (vec, color) in self.data {
+            chart.draw_series(LineSeries::new(vec.iter().copied().enumerate(), *color))?;
+        }
+
+        for peak in self.peaks {
+            chart.draw_series(LineSeries::new(
+                [(peak.pos as usize, 0.0), (peak.pos as usize, peak.height)],
                 &BLUE,
-            ))
-            .unwrap();
+            ))?;
 
-        chart
-            .draw_series(DashedLineSeries::new(
+            chart.draw_series(DashedLineSeries::new(
                 [
                     ((peak.pos - peak.width / 2.) as usize, peak.height / 2.),
                     ((peak.pos + peak.width / 2.) as usize, peak.height / 2.),
-                ]
-                .into_iter(),
+                ],
                 4,
                 1,
                 ORANGE.into(),
-            ))
-            .unwrap();
+            ))?;
+        }
+
+        Ok(())
     }
+}
 
-    root.present().unwrap();
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
 }
 
-pub fn simple_graph_vec(path: impl AsRef<Path>, data: &DVector<f64>) {
+/// Plots `data` and `peaks` to `path`, see [`Figure::save`] for the supported formats.
+pub fn simple_graph_vecs_with_peaks(
+    path: impl AsRef<Path>,
+    data: &[(&DVector<f64>, &RGBColor)],
+    peaks: &[Peak],
+) -> Result<(), Box<dyn Error>> {
     let path = path.as_ref();
-    let root = BitMapBackend::new(path, (1280, 720)).into_drawing_area();
-    root.fill(&WHITE).unwrap();
-    let mut chart = ChartBuilder::on(&root)
-        .caption(
-            path.components()
-                .last()
-                .unwrap()
-                .as_os_str()
-                .to_string_lossy(),
-            ("sans-serif", 50).into_font(),
-        )
-        .margin(10)
-        .x_label_area_size(30)
-        .y_label_area_size(30)
-        .build_cartesian_2d(0..data.len(), data.min()..data.max())
-        .unwrap();
-
-    chart.configure_mesh().draw().unwrap();
-
-    chart
-        .draw_series(LineSeries::new(data.iter().copied().enumerate(), &RED))
-        .unwrap();
-
-    root.present().unwrap();
+
+    SignalPlot {
+        title: file_name(path),
+        size: (3840, 2160),
+        data,
+        peaks,
+    }
+    .save(path)
 }
 
-pub fn simple_graph_vec_with_peaks(path: impl AsRef<Path>, data: &DVector<f64>, peaks: &[Peak]) {
+pub fn simple_graph_vec(path: impl AsRef<Path>, data: &DVector<f64>) -> Result<(), Box<dyn Error>> {
     let path = path.as_ref();
-    let root = BitMapBackend::new(path, (3840, 2160)).into_drawing_area();
-    root.fill(&WHITE).unwrap();
-    let mut chart = ChartBuilder::on(&root)
-        .caption(
-            path.components()
-                .last()
-                .unwrap()
-                .as_os_str()
-                .to_string_lossy(),
-            ("sans-serif", 50).into_font(),
-        )
-        .margin(10)
-        .x_label_area_size(30)
-        .y_label_area_size(30)
-        .build_cartesian_2d(0..data.len(), data.min()..data.max())
-        .unwrap();
-
-    chart.configure_mesh().draw().unwrap();
-
-    chart
-        .draw_series(LineSeries::new(data.iter().copied().enumerate(), &RED))
-        .unwrap();
-
-    for peak in peaks {
-        chart
-            .draw_series(LineSeries::new(
-                [(peak.pos as usize, 0.0), (peak.pos as usize, peak.height)].into_iter(),
-                &BLUE,
-            ))
-            .unwrap();
 
-        chart
-            .draw_series(DashedLineSeries::new(
-                [
-                    ((peak.pos - peak.width / 2.) as usize, peak.height / 2.),
-                    ((peak.pos + peak.width / 2.) as usize, peak.height / 2.),
-                ]
-                .into_iter(),
-                4,
-                1,
-                ORANGE.into(),
-            ))
-            .unwrap();
+    SignalPlot {
+        title: file_name(path),
+        size: (1280, 720),
+        data: &[(data, &RED)],
+        peaks: &[],
     }
+    .save(path)
+}
 
-    root.present().unwrap();
+pub fn simple_graph_vec_with_peaks(
+    path: impl AsRef<Path>,
+    data: &DVector<f64>,
+    peaks: &[Peak],
+) -> Result<(), Box<dyn Error>> {
+    let path = path.as_ref();
+
+    SignalPlot {
+        title: file_name(path),
+        size: (3840, 2160),
+        data: &[(data, &RED)],
+        peaks,
+    }
+    .save(path)
 }
@@ -5,7 +5,7 @@ use std::{
 };
 
 use itertools::Itertools;
-use nalgebra::DVector;
+use nalgebra::{DMatrix, DVector};
 use statrs::statistics::Statistics;
 
 const PEAK_SIGMA_THRESHOLD_MULT: f64 = -2.;
@@ -40,12 +40,7 @@ fn gauss_2nd_derivative(scale: f64, x: f64) -> f64 {
 fn generate_2dog_kernel(scale: f64) -> DVector<f64> {
     let n = 6 * scale as usize + 1;
     let mut kernel: DVector<f64> = DVector::zeros(n);
-    let xcord = |i: usize| -> f64 {
-        let i = i as f64;
-        let coord = i - (n / 2) as f64;
-
-        coord
-    };
+    let xcord = |i: usize| -> f64 { i as f64 - (n / 2) as f64 };
 
     (0..n).for_each(|v| kernel[v] = gauss_2nd_derivative(scale, xcord(v)));
 
@@ -70,76 +65,66 @@ fn find_minima(data: &DVector<f64>, threshold: f64) -> Vec<(usize, f64)> {
         .collect()
 }
 
-fn combine_peaks(peaks: &[Peak]) -> Peak {
-    let mut weighted_width = 0.0;
-    let mut weighted_height = 0.0;
-    let mut weighted_pos = 0.0;
-    let mut total_prominence = 0.0;
-
-    let mut mp = 0.;
-    let mut best_pos = 0.;
-
-    for p in peaks {
-        weighted_width += p.width * p.prominence;
-        weighted_height += p.height * p.prominence;
-        weighted_pos += (p.pos + 0.5 * p.width) * p.prominence;
-        total_prominence += p.prominence;
-
-        if p.prominence > mp {
-            mp = p.prominence;
-            best_pos = p.pos
-        }
-    }
-
-    Peak {
-        width: weighted_width / total_prominence,
-        height: weighted_height / total_prominence,
-        pos: best_pos,
-        prominence: total_prominence,
-    }
-}
-
 impl DDOGPeakDetector {
     pub fn new(scales: Vec<f64>) -> Self {
         Self { scales }
     }
-}
 
-impl PeakDetector for DDOGPeakDetector {
-    fn detect_peaks(&self, signal: &DVector<f64>) -> Vec<Peak> {
-        let threshold = signal.view_range(0..250, 0).std_dev() * PEAK_SIGMA_THRESHOLD_MULT;
+    pub fn scales(&self) -> &[f64] {
+        &self.scales
+    }
+
+    /// Minima below this response count as peaks, derived from the noise at the start of the
+    /// signal.
+    pub fn threshold(&self, signal: &DVector<f64>) -> f64 {
+        signal.view_range(0..250.min(signal.len()), 0).std_dev() * PEAK_SIGMA_THRESHOLD_MULT
+    }
 
-        let conv_minima = self
+    /// Response of the signal to the wavelet at every scale, one row per scale.
+    pub fn responses(&self, signal: &DVector<f64>) -> DMatrix<f64> {
+        let rows = self
             .scales
             .iter()
-            .map(|&scale| signal.convolve_same(generate_2dog_kernel(scale)))
-            .map(|conv| {
-                find_minima(&conv, threshold)
-                    .into_iter()
-                    .collect::<Vec<(usize, f64)>>()
+            .map(|&scale| {
+                signal
+                    .convolve_same(generate_2dog_kernel(scale))
+                    .transpose()
             })
-            .collect::<Vec<Vec<(usize, f64)>>>();
-
-        let mut peaks: Vec<Peak> = conv_minima
-            .into_iter()
-            .enumerate()
-            .flat_map(|(size_index, vec)| {
-                vec.into_iter().map(move |(pos, prominence)| Peak {
-                    width: self.scales[size_index],
-                    height: signal[pos],
-                    prominence: -prominence,
-                    pos: pos as f64,
-                })
-            })
-            .collect();
-
-        let mut new_peaks: Vec<Peak> = vec![];
+            .collect::<Vec<_>>();
 
-        for peak in &peaks {
-            println!("{:?}", peak);
+        if rows.is_empty() {
+            return DMatrix::zeros(0, signal.len());
         }
 
-        while peaks.len() > 0 {
+        DMatrix::from_rows(&rows)
+    }
+
+    /// Response minima at every scale, before they are grouped into peaks.
+    pub fn ridge_points(&self, signal: &DVector<f64>, responses: &DMatrix<f64>) -> Vec<Peak> {
+        let threshold = self.threshold(signal);
+
+        responses
+            .row_iter()
+            .zip(&self.scales)
+            .flat_map(|(row, &scale)| {
+                find_minima(&row.transpose(), threshold).into_iter().map(
+                    move |(pos, prominence)| Peak {
+                        width: scale,
+                        height: signal[pos],
+                        prominence: -prominence,
+                        pos: pos as f64,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Groups ridge points that are closer to each other than their scales, each group becomes
+    /// one peak.
+    pub fn group(mut peaks: Vec<Peak>) -> Vec<Vec<Peak>> {
+        let mut groups = vec![];
+
+        while !peaks.is_empty() {
             let mut queue = vec![peaks.remove(0)];
             let mut group = vec![];
 
@@ -164,91 +149,58 @@ impl PeakDetector for DDOGPeakDetector {
                 group.push(ref_peak);
             }
 
-            new_peaks.push(combine_peaks(&group));
+            groups.push(group);
         }
 
-        new_peaks
+        groups
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use nalgebra::{DMatrix, DVector};
-    use plotters::{
-        chart::ChartBuilder,
-        prelude::{BitMapBackend, IntoDrawingArea, VulcanoHSL},
-        series::SurfaceSeries,
-        style::{BLACK, Color, GREEN, RED, WHITE},
-    };
 
-    use crate::peak_detection::{DDOGPeakDetector, PeakDetector, generate_2dog_kernel};
+    /// Combines a group into one peak at its most prominent point, with width and height
+    /// weighted by prominence.
+    pub fn combine(peaks: &[Peak]) -> Peak {
+        let mut weighted_width = 0.0;
+        let mut weighted_height = 0.0;
+        let mut total_prominence = 0.0;
 
-    #[test]
-    fn ridge_graph() {
-        let data = crate::io::read_series("../../gc-data/R16443 - Jun 08 2025, 09;24.fusion-data")
-            .unwrap();
+        let mut mp = 0.;
+        let mut best_pos = 0.;
 
-        const START: usize = 5;
-        const END: usize = 101;
+        for p in peaks {
+            weighted_width += p.width * p.prominence;
+            weighted_height += p.height * p.prominence;
+            total_prominence += p.prominence;
 
-        let m: Vec<f64> = (START..END)
-            .map(|n| generate_2dog_kernel(n as f64))
-            .flat_map(|k| {
-                data.convolve_same(k)
-                    .into_iter()
-                    .map(|v| *v)
-                    .collect::<Vec<f64>>()
-            })
-            .collect();
-
-        let disp: DMatrix<f64> = DMatrix::from_row_slice(END - START, data.len(), &m);
-
-        let disp = disp.add_scalar(disp.min().abs());
-        let disp = disp.scale(1. / disp.max());
-
-        println!("{:?}, {}, {}", disp.shape(), disp.max(), disp.min());
-
-        let root = BitMapBackend::gif("test-img/ridge_graph.gif", (1920, 1080), 100)
-            .unwrap()
-            .into_drawing_area();
-
-        for pitch in 0..157 {
-            root.fill(&WHITE).unwrap();
-
-            let mut chart = ChartBuilder::on(&root)
-                .caption("CWT Wavelet Space!", ("sans-serif", 20))
-                .build_cartesian_3d(START..END, 0.0..1.0, 0..(data.len() / 1000))
-                .unwrap();
-
-            chart.with_projection(|mut p| {
-                p.pitch = 1.57 - (1.57 - pitch as f64 / 50.0).abs();
-                p.scale = 0.7;
-                p.into_matrix()
-            });
-
-            chart
-                .configure_axes()
-                .light_grid_style(BLACK.mix(0.15))
-                .max_light_lines(3)
-                .draw()
-                .unwrap();
-
-            chart
-                .draw_series(
-                    SurfaceSeries::xoz(0..(END - START), 0..(data.len() / 1000), |x, y| {
-                        disp.row(x)[y * 1000]
-                    })
-                    .style_func(&|&v| VulcanoHSL::get_color(v).into()),
-                )
-                .unwrap();
+            if p.prominence > mp {
+                mp = p.prominence;
+                best_pos = p.pos
+            }
+        }
 
-            root.present().unwrap();
+        Peak {
+            width: weighted_width / total_prominence,
+            height: weighted_height / total_prominence,
+            pos: best_pos,
+            prominence: total_prominence,
         }
+    }
+}
 
-        root.present().unwrap();
+impl PeakDetector for DDOGPeakDetector {
+    fn detect_peaks(&self, signal: &DVector<f64>) -> Vec<Peak> {
+        let ridge_points = self.ridge_points(signal, &self.responses(signal));
 
-        panic!()
+        Self::group(ridge_points)
+            .iter()
+            .map(|group| Self::combine(group))
+            .collect()
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use plotters::style::{GREEN, RED};
+
+    use crate::peak_detection::{DDOGPeakDetector, PeakDetector, generate_2dog_kernel};
 
     #[test]
     fn print_2dog_kernel() {
@@ -257,7 +209,7 @@ mod tests {
 
         assert_eq!(kernel.argmin().0, kernel.len() / 2);
 
-        crate::simple_graph_vec("test-img/2dog_kernel_6sigma_test.png", &kernel);
+        crate::simple_graph_vec("test-img/2dog_kernel_6sigma_test.png", &kernel).unwrap();
     }
 
     #[test]
@@ -273,8 +225,7 @@ mod tests {
             "test-img/2dog_peaks_test.png",
             &[(&data, &RED), (&convolution, &GREEN)],
             &peaks,
-        );
-
-        //panic!("give me stdout bitch");
+        )
+        .unwrap();
     }
 }
@@ -0,0 +1,497 @@
+use std::{error::Error, path::Path};
+
+use nalgebra::DVector;
+use plotters::{
+    coord::{Shift, types::RangedCoordf64},
+    prelude::*,
+    style::text_anchor::{HPos, Pos, VPos},
+};
+
+use crate::io::{Detector, IntegratedPeak};
+
+mod comparison;
+mod scalogram;
+
+pub use comparison::{Comparison, Layout};
+pub use scalogram::Scalogram;
+
+/// Output format of [`Figure::save`], picked from the file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// Raster image, encoded as PNG, JPEG or BMP depending on the extension.
+    Bitmap,
+    /// Scalable vector graphics, for print and for zooming into trace peaks.
+    Svg,
+}
+
+impl ImageFormat {
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        match path.as_ref().extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("svg") => ImageFormat::Svg,
+            _ => ImageFormat::Bitmap,
+        }
+    }
+}
+
+/// Something that can be drawn onto any plotters backend.
+pub trait Figure {
+    /// Size in pixels.
+    fn size(&self) -> (u32, u32);
+
+    /// Draws onto a drawing area, e.g. one panel of a larger figure.
+    fn draw<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>) -> Result<(), Box<dyn Error>>
+    where
+        DB::ErrorType: 'static;
+
+    /// Writes the figure to `path` in the [`ImageFormat`] its extension asks for.
+    fn save(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>>
+    where
+        Self: Sized,
+    {
+        let path = path.as_ref();
+
+        match ImageFormat::from_path(path) {
+            ImageFormat::Bitmap => {
+                let root = BitMapBackend::new(path, self.size()).into_drawing_area();
+                self.draw(&root)?;
+                root.present()?;
+            }
+            ImageFormat::Svg => {
+                let root = SVGBackend::new(path, self.size()).into_drawing_area();
+                self.draw(&root)?;
+                root.present()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The figure as an SVG document, e.g. for embedding in HTML.
+    fn to_svg(&self) -> Result<String, Box<dyn Error>>
+    where
+        Self: Sized,
+    {
+        let mut svg = String::new();
+
+        {
+            let root = SVGBackend::with_string(&mut svg, self.size()).into_drawing_area();
+            self.draw(&root)?;
+            root.present()?;
+        }
+
+        Ok(svg)
+    }
+}
+
+/// Grid, axes and tick labels against retention time, with tick precision following the
+/// visible range.
+fn draw_mesh<DB: DrawingBackend>(
+    chart: &mut ChartContext<'_, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+    theme: &Theme,
+    y_desc: &str,
+) -> Result<(), Box<dyn Error>>
+where
+    DB::ErrorType: 'static,
+{
+    let font = (theme.font.as_str(), theme.font_size)
+        .into_font()
+        .color(&theme.text);
+    let x_span = chart.x_range().end - chart.x_range().start;
+    let y_span = chart.y_range().end - chart.y_range().start;
+    let precision = |span: f64| if span < 10. { 2 } else { 0 };
+
+    chart
+        .configure_mesh()
+        .x_desc("Retention time (s)")
+        .y_desc(y_desc)
+        .x_label_formatter(&|t| format!("{:.*}", precision(x_span).min(1), t))
+        .y_label_formatter(&|v| format!("{:.*}", precision(y_span), v))
+        .axis_style(theme.text)
+        .bold_line_style(theme.grid)
+        .light_line_style(theme.grid.mix(0.4))
+        .label_style(font.clone())
+        .axis_desc_style(font)
+        .draw()?;
+
+    Ok(())
+}
+
+/// Colours and font of a [`Chromatogram`], [`Comparison`] or [`Scalogram`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub background: RGBColor,
+    /// Axes, captions and labels.
+    pub text: RGBColor,
+    pub grid: RGBColor,
+    pub trace: RGBColor,
+    /// Shading of integrated peak areas.
+    pub fill: RGBColor,
+    pub fill_opacity: f64,
+    pub baseline: RGBColor,
+    pub font: String,
+    /// Font size of labels, captions are drawn larger.
+    pub font_size: u32,
+}
+
+impl Theme {
+    pub fn light() -> Self {
+        Self {
+            background: WHITE,
+            text: BLACK,
+            grid: RGBColor(220, 220, 220),
+            trace: RGBColor(31, 73, 125),
+            fill: RGBColor(79, 129, 189),
+            fill_opacity: 0.35,
+            baseline: RGBColor(192, 80, 77),
+            font: "sans-serif".into(),
+            font_size: 14,
+        }
+    }
+
+    pub fn dark() -> Self {
+        Self {
+            background: RGBColor(30, 30, 30),
+            text: RGBColor(230, 230, 230),
+            grid: RGBColor(70, 70, 70),
+            trace: RGBColor(120, 190, 255),
+            fill: RGBColor(120, 190, 255),
+            fill_opacity: 0.3,
+            baseline: RGBColor(255, 140, 100),
+            font: "sans-serif".into(),
+            font_size: 14,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::light()
+    }
+}
+
+/// Renders a detector signal against retention time with the integrated peaks shaded down to
+/// their baselines and labelled at their apex.
+///
+/// Every zoom window is drawn as an extra panel below the full chromatogram, with the signal axis
+/// scaled to the window so that trace peaks become visible.
+#[derive(Debug, Clone)]
+pub struct Chromatogram<'a> {
+    values: &'a DVector<f64>,
+    values_per_second: f64,
+    peaks: &'a [IntegratedPeak],
+    title: String,
+    trace_label: String,
+    size: (u32, u32),
+    theme: Theme,
+    zooms: Vec<(f64, f64)>,
+    labels: bool,
+    legend: bool,
+}
+
+impl<'a> Chromatogram<'a> {
+    pub fn new(values: &'a DVector<f64>, values_per_second: f64) -> Self {
+        Self {
+            values,
+            values_per_second,
+            peaks: &[],
+            title: String::new(),
+            trace_label: "Signal".into(),
+            size: (1600, 900),
+            theme: Theme::default(),
+            zooms: vec![],
+            labels: true,
+            legend: true,
+        }
+    }
+
+    /// The signal and integrated peaks of `detector`.
+    pub fn from_detector(detector: &'a Detector) -> Self {
+        Self::new(&detector.values, detector.values_per_second)
+            .with_peaks(&detector.peaks)
+            .with_trace_label(&detector.name)
+    }
+
+    pub fn with_peaks(mut self, peaks: &'a [IntegratedPeak]) -> Self {
+        self.peaks = peaks;
+        self
+    }
+
+    pub fn with_title(mut self, title: &str) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Name of the signal in the legend.
+    pub fn with_trace_label(mut self, label: &str) -> Self {
+        self.trace_label = label.into();
+        self
+    }
+
+    /// Size in pixels.
+    pub fn with_size(mut self, width: u32, height: u32) -> Self {
+        self.size = (width, height);
+        self
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Adds a zoomed panel from `start` to `end` seconds.
+    pub fn with_zoom(mut self, start: f64, end: f64) -> Self {
+        self.zooms.push((start.min(end), start.max(end)));
+        self
+    }
+
+    /// Whether to label peaks with their compound, on by default.
+    pub fn with_labels(mut self, labels: bool) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Whether to draw a legend, on by default.
+    pub fn with_legend(mut self, legend: bool) -> Self {
+        self.legend = legend;
+        self
+    }
+
+    fn index(&self, time: f64) -> usize {
+        ((time * self.values_per_second).round().max(0.) as usize)
+            .min(self.values.len().saturating_sub(1))
+    }
+
+    fn time(&self, index: usize) -> f64 {
+        index as f64 / self.values_per_second
+    }
+
+    fn value_at(&self, time: f64) -> f64 {
+        self.values[self.index(time)]
+    }
+
+    /// Integrated peaks overlapping `window`.
+    fn visible_peaks(&self, (start, end): (f64, f64)) -> impl Iterator<Item = &IntegratedPeak> {
+        self.peaks
+            .iter()
+            .filter(move |p| p.area > 0. && p.end >= start && p.start <= end)
+    }
+
+    /// Signal above the peak's baseline, clipped to `window`. Without baseline levels from the
+    /// instrument the baseline runs straight between the signal at the peak's start and end.
+    fn peak_polygon(&self, peak: &IntegratedPeak, (start, end): (f64, f64)) -> Vec<(f64, f64)> {
+        let (from, to) = (peak.start.max(start), peak.end.min(end));
+        let (base_start, base_end) = peak
+            .baseline
+            .map_or((self.value_at(peak.start), self.value_at(peak.end)), |b| {
+                (b.start, b.end)
+            });
+        let baseline = |t: f64| {
+            if peak.end > peak.start {
+                base_start + (base_end - base_start) * (t - peak.start) / (peak.end - peak.start)
+            } else {
+                base_start
+            }
+        };
+
+        (self.index(from)..=self.index(to))
+            .map(|i| (self.time(i), self.values[i]))
+            .chain([(to, baseline(to)), (from, baseline(from))])
+            .collect()
+    }
+
+    fn panel<DB: DrawingBackend>(
+        &self,
+        area: &DrawingArea<DB, Shift>,
+        window: (f64, f64),
+        caption: Option<&str>,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        DB::ErrorType: 'static,
+    {
+        let theme = &self.theme;
+        let font = (theme.font.as_str(), theme.font_size)
+            .into_font()
+            .color(&theme.text);
+
+        let samples = self.index(window.0)..=self.index(window.1);
+        let (min, max) = samples
+            .clone()
+            .map(|i| self.values[i])
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+                (lo.min(v), hi.max(v))
+            });
+        let span = if max > min { max - min } else { 1. };
+        // headroom for the apex labels
+        let y_range = (min - 0.02 * span)..(max + 0.12 * span);
+
+        let mut builder = ChartBuilder::on(area);
+        builder
+            .margin(10)
+            .x_label_area_size(3 * theme.font_size)
+            .y_label_area_size(6 * theme.font_size);
+
+        if let Some(caption) = caption {
+            builder.caption(
+                caption,
+                (theme.font.as_str(), theme.font_size * 3 / 2)
+                    .into_font()
+                    .color(&theme.text),
+            );
+        }
+
+        let mut chart = builder.build_cartesian_2d(window.0..window.1, y_range)?;
+
+        draw_mesh(&mut chart, theme, "Signal")?;
+
+        let fill = theme.fill.mix(theme.fill_opacity);
+
+        chart
+            .draw_series(
+                self.visible_peaks(window)
+                    .map(|p| Polygon::new(self.peak_polygon(p, window), fill.filled())),
+            )?
+            .label("Integrated area")
+            .legend(move |(x, y)| Rectangle::new([(x, y - 5), (x + 20, y + 5)], fill.filled()));
+
+        chart
+            .draw_series(self.visible_peaks(window).map(|p| {
+                let (from, to) = (p.start.max(window.0), p.end.min(window.1));
+                let polygon = self.peak_polygon(p, window);
+                let n = polygon.len();
+
+                PathElement::new(
+                    vec![(from, polygon[n - 1].1), (to, polygon[n - 2].1)],
+                    theme.baseline,
+                )
+            }))?
+            .label("Baseline")
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], theme.baseline));
+
+        chart
+            .draw_series(LineSeries::new(
+                samples.map(|i| (self.time(i), self.values[i])),
+                theme.trace.stroke_width(1),
+            ))?
+            .label(self.trace_label.as_str())
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], theme.trace));
+
+        if self.labels {
+            let anchor = font.pos(Pos::new(HPos::Center, VPos::Bottom));
+
+            chart.draw_series(
+                self.visible_peaks(window)
+                    .filter(|p| (window.0..=window.1).contains(&p.top))
+                    .filter_map(|p| p.label.as_ref().map(|label| (p, label)))
+                    .map(|(p, label)| {
+                        EmptyElement::at((p.top, self.value_at(p.top)))
+                            + Text::new(label.clone(), (0, -4), anchor.clone())
+                    }),
+            )?;
+        }
+
+        if self.legend {
+            chart
+                .configure_series_labels()
+                .position(SeriesLabelPosition::UpperRight)
+                .background_style(theme.background.mix(0.85))
+                .border_style(theme.text)
+                .label_font(font)
+                .draw()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Figure for Chromatogram<'_> {
+    fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn draw<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>) -> Result<(), Box<dyn Error>>
+    where
+        DB::ErrorType: 'static,
+    {
+        if self.values.is_empty() {
+            return Err("Nothing to plot.".into());
+        }
+
+        root.fill(&self.theme.background)?;
+
+        let duration = self.values.len() as f64 / self.values_per_second;
+        let title = (!self.title.is_empty()).then_some(self.title.as_str());
+
+        if self.zooms.is_empty() {
+            return self.panel(root, (0., duration), title);
+        }
+
+        let (main, zoomed) = root.split_vertically(root.relative_to_height(0.6));
+        self.panel(&main, (0., duration), title)?;
+
+        for (area, &(start, end)) in zoomed
+            .split_evenly((1, self.zooms.len()))
+            .iter()
+            .zip(&self.zooms)
+        {
+            self.panel(
+                area,
+                (start, end),
+                Some(&format!("{:.1} - {:.1} s", start, end)),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::DVector;
+
+    use crate::io::{Detector, read_run};
+
+    use super::*;
+
+    #[test]
+    fn chromatogram() {
+        let run = read_run("../../gc-data/R16443 - Jun 08 2025, 09;24.fusion-data").unwrap();
+        let detector = run.detector().unwrap();
+
+        Chromatogram::from_detector(detector)
+            .with_title(&format!("{} ({})", run.name, run.timestamp))
+            .with_zoom(55., 65.)
+            .with_zoom(78., 100.)
+            .save("test-img/chromatogram_test.png")
+            .unwrap();
+
+        let svg = Chromatogram::from_detector(detector)
+            .with_size(800, 450)
+            .to_svg()
+            .unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("R-1234yf"));
+
+        let empty = Detector {
+            values: DVector::zeros(0),
+            ..detector.clone()
+        };
+        assert_eq!(
+            Chromatogram::from_detector(&empty)
+                .to_svg()
+                .unwrap_err()
+                .to_string(),
+            "Nothing to plot."
+        );
+
+        assert_eq!(ImageFormat::from_path("a/b.SVG"), ImageFormat::Svg);
+        assert_eq!(ImageFormat::from_path("a/b.png"), ImageFormat::Bitmap);
+
+        let chromatogram = Chromatogram::from_detector(detector).with_theme(Theme::dark());
+        let polygon = chromatogram.peak_polygon(&detector.peaks[4], (0., 140.));
+        assert!(polygon.len() > 2);
+        assert_eq!(
+            chromatogram.value_at(1e9),
+            detector.values[detector.values.len() - 1]
+        );
+    }
+}
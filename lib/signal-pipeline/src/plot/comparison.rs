@@ -0,0 +1,411 @@
+use std::error::Error;
+
+use plotters::{
+    coord::Shift,
+    prelude::*,
+    style::text_anchor::{HPos, Pos, VPos},
+};
+
+use crate::io::{Detector, IntegratedPeak, Run};
+
+use super::{Figure, Theme, draw_mesh};
+
+/// How a [`Comparison`] arranges its runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Layout {
+    /// All runs in one chart.
+    #[default]
+    Overlay,
+    /// One chart per run, top to bottom, sharing the retention time axis.
+    Stacked,
+}
+
+/// Several runs plotted against retention time, e.g. a sample against its reference standard or
+/// the same cylinder over time.
+///
+/// Runs can be shifted so that a reference peak elutes at the same time as in the first run, and
+/// scaled so that the reference peak has a height of 1.
+#[derive(Debug, Clone)]
+pub struct Comparison<'a> {
+    traces: Vec<(String, &'a Detector)>,
+    layout: Layout,
+    align_on: Option<String>,
+    normalize_on: Option<String>,
+    window: Option<(f64, f64)>,
+    title: String,
+    size: (u32, u32),
+    theme: Theme,
+    labels: bool,
+}
+
+/// A run shifted and scaled for plotting.
+struct Trace {
+    label: String,
+    points: Vec<(f64, f64)>,
+    /// Compound labels at their apex.
+    apexes: Vec<(String, (f64, f64))>,
+}
+
+fn reference_peak<'d>(detector: &'d Detector, label: &str) -> Option<&'d IntegratedPeak> {
+    detector.peaks.iter().find(|p| {
+        p.area > 0.
+            && p.label
+                .as_deref()
+                .is_some_and(|l| l.eq_ignore_ascii_case(label))
+    })
+}
+
+impl<'a> Comparison<'a> {
+    pub fn new(layout: Layout) -> Self {
+        Self {
+            traces: vec![],
+            layout,
+            align_on: None,
+            normalize_on: None,
+            window: None,
+            title: String::new(),
+            size: (1600, 900),
+            theme: Theme::default(),
+            labels: true,
+        }
+    }
+
+    /// Adds the only detector of `run`, labelled with its sample name and timestamp.
+    pub fn with_run(self, run: &'a Run) -> Result<Self, String> {
+        let detector = run.detector().map_err(|e| e.to_string())?;
+        let label = if run.name.is_empty() {
+            run.id.clone()
+        } else {
+            format!("{} ({})", run.name, run.timestamp)
+        };
+
+        Ok(self.with_trace(&label, detector))
+    }
+
+    pub fn with_trace(mut self, label: &str, detector: &'a Detector) -> Self {
+        self.traces.push((label.into(), detector));
+        self
+    }
+
+    /// Shifts every run so that the peak labelled `label` has its apex where it has in the first
+    /// run.
+    pub fn with_alignment(mut self, label: &str) -> Self {
+        self.align_on = Some(label.into());
+        self
+    }
+
+    /// Scales every run so that the peak labelled `label` has a height of 1.
+    pub fn with_normalization(mut self, label: &str) -> Self {
+        self.normalize_on = Some(label.into());
+        self
+    }
+
+    /// Only plots from `start` to `end` seconds.
+    pub fn with_window(mut self, start: f64, end: f64) -> Self {
+        self.window = Some((start.min(end), start.max(end)));
+        self
+    }
+
+    pub fn with_title(mut self, title: &str) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Size in pixels.
+    pub fn with_size(mut self, width: u32, height: u32) -> Self {
+        self.size = (width, height);
+        self
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Whether to label peaks with their compound, on by default. Overlays only label the first
+    /// run.
+    pub fn with_labels(mut self, labels: bool) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Time shift of every run, see [`Self::with_alignment`].
+    pub fn shifts(&self) -> Result<Vec<f64>, String> {
+        let Some(label) = &self.align_on else {
+            return Ok(vec![0.; self.traces.len()]);
+        };
+
+        let tops = self
+            .traces
+            .iter()
+            .map(|(name, detector)| {
+                reference_peak(detector, label)
+                    .map(|p| p.top)
+                    .ok_or(format!("{} has no {} peak to align on.", name, label))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(tops.iter().map(|top| tops[0] - top).collect())
+    }
+
+    /// Factor every run's signal is multiplied with, see [`Self::with_normalization`].
+    pub fn scales(&self) -> Result<Vec<f64>, String> {
+        let Some(label) = &self.normalize_on else {
+            return Ok(vec![1.; self.traces.len()]);
+        };
+
+        self.traces
+            .iter()
+            .map(|(name, detector)| {
+                reference_peak(detector, label)
+                    .filter(|p| p.height > 0.)
+                    .map(|p| 1. / p.height)
+                    .ok_or(format!("{} has no {} peak to normalize by.", name, label))
+            })
+            .collect()
+    }
+
+    fn window(&self, shifts: &[f64]) -> (f64, f64) {
+        self.window.unwrap_or_else(|| {
+            self.traces
+                .iter()
+                .zip(shifts)
+                .map(|((_, d), shift)| {
+                    (*shift, d.values.len() as f64 / d.values_per_second + shift)
+                })
+                .fold(
+                    (f64::INFINITY, f64::NEG_INFINITY),
+                    |(lo, hi), (start, end)| (lo.min(start), hi.max(end)),
+                )
+        })
+    }
+
+    fn prepare(&self, window: (f64, f64), shifts: &[f64], scales: &[f64]) -> Vec<Trace> {
+        let visible = |t: f64| (window.0..=window.1).contains(&t);
+
+        self.traces
+            .iter()
+            .zip(shifts.iter().zip(scales))
+            .map(|((label, detector), (&shift, &scale))| {
+                let point = |i: usize| (detector.time(i) + shift, detector.values[i] * scale);
+
+                Trace {
+                    label: label.clone(),
+                    points: (0..detector.values.len())
+                        .map(point)
+                        .filter(|(t, _)| visible(*t))
+                        .collect(),
+                    apexes: detector
+                        .peaks
+                        .iter()
+                        .filter(|p| p.area > 0. && visible(p.top + shift))
+                        .filter_map(|p| {
+                            let index = (p.top * detector.values_per_second).round() as usize;
+
+                            p.label
+                                .clone()
+                                .zip((index < detector.values.len()).then(|| point(index)))
+                        })
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+
+    fn panel<DB: DrawingBackend>(
+        &self,
+        area: &DrawingArea<DB, Shift>,
+        window: (f64, f64),
+        traces: &[(usize, &Trace)],
+        caption: Option<&str>,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        DB::ErrorType: 'static,
+    {
+        let theme = &self.theme;
+        let font = (theme.font.as_str(), theme.font_size)
+            .into_font()
+            .color(&theme.text);
+
+        let (min, max) = traces
+            .iter()
+            .flat_map(|(_, t)| t.points.iter().map(|p| p.1))
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+                (lo.min(v), hi.max(v))
+            });
+        let (min, max) = if min <= max { (min, max) } else { (0., 1.) };
+        let span = if max > min { max - min } else { 1. };
+
+        let mut builder = ChartBuilder::on(area);
+        builder
+            .margin(10)
+            .x_label_area_size(3 * theme.font_size)
+            .y_label_area_size(6 * theme.font_size);
+
+        if let Some(caption) = caption {
+            builder.caption(
+                caption,
+                (theme.font.as_str(), theme.font_size * 3 / 2)
+                    .into_font()
+                    .color(&theme.text),
+            );
+        }
+
+        let mut chart = builder
+            .build_cartesian_2d(window.0..window.1, (min - 0.02 * span)..(max + 0.12 * span))?;
+
+        let y_desc = match &self.normalize_on {
+            Some(label) => format!("Signal relative to {}", label),
+            None => "Signal".into(),
+        };
+        draw_mesh(&mut chart, theme, &y_desc)?;
+
+        for &(i, trace) in traces {
+            let color = Palette99::pick(i).to_rgba();
+
+            chart
+                .draw_series(LineSeries::new(trace.points.iter().copied(), color))?
+                .label(trace.label.as_str())
+                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+        }
+
+        if self.labels {
+            let anchor = font.pos(Pos::new(HPos::Center, VPos::Bottom));
+
+            if let Some((_, trace)) = traces.first() {
+                chart.draw_series(trace.apexes.iter().map(|(label, apex)| {
+                    EmptyElement::at(*apex) + Text::new(label.clone(), (0, -4), anchor.clone())
+                }))?;
+            }
+        }
+
+        chart
+            .configure_series_labels()
+            .position(SeriesLabelPosition::UpperRight)
+            .background_style(theme.background.mix(0.85))
+            .border_style(theme.text)
+            .label_font(font)
+            .draw()?;
+
+        Ok(())
+    }
+}
+
+impl Figure for Comparison<'_> {
+    fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn draw<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>) -> Result<(), Box<dyn Error>>
+    where
+        DB::ErrorType: 'static,
+    {
+        if self.traces.is_empty() {
+            return Err("Nothing to compare.".into());
+        }
+
+        let shifts = self.shifts()?;
+        let scales = self.scales()?;
+        let window = self.window(&shifts);
+        let traces = self.prepare(window, &shifts, &scales);
+
+        root.fill(&self.theme.background)?;
+
+        let title = (!self.title.is_empty()).then_some(self.title.as_str());
+
+        match self.layout {
+            Layout::Overlay => self.panel(
+                root,
+                window,
+                &traces.iter().enumerate().collect::<Vec<_>>(),
+                title,
+            ),
+            Layout::Stacked => {
+                let root = match title {
+                    Some(title) => root.titled(
+                        title,
+                        (self.theme.font.as_str(), self.theme.font_size * 3 / 2)
+                            .into_font()
+                            .color(&self.theme.text),
+                    )?,
+                    None => root.clone(),
+                };
+
+                for (area, trace) in root
+                    .split_evenly((traces.len(), 1))
+                    .iter()
+                    .zip(traces.iter().enumerate())
+                {
+                    self.panel(area, window, &[trace], None)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::io::read_run;
+
+    use super::*;
+
+    #[test]
+    fn overlay_and_stack() {
+        let sample = read_run("../../gc-data/R16443 - Jun 08 2025, 09;24.fusion-data").unwrap();
+        let reference = read_run("../../gc-data/0374 - Jun 08 2025, 08;24.fusion-data").unwrap();
+
+        let comparison = Comparison::new(Layout::Overlay)
+            .with_run(&reference)
+            .unwrap()
+            .with_run(&sample)
+            .unwrap()
+            .with_alignment("R-22")
+            .with_normalization("R-22")
+            .with_window(55., 80.);
+
+        let shifts = comparison.shifts().unwrap();
+        let scales = comparison.scales().unwrap();
+        let traces = comparison.prepare((55., 80.), &shifts, &scales);
+
+        assert_eq!(shifts[0], 0.);
+        // the reference peaks end up on top of each other at a height of 1
+        let apexes = traces
+            .iter()
+            .map(|trace| {
+                trace
+                    .apexes
+                    .iter()
+                    .find(|(label, _)| label == "R-22")
+                    .unwrap()
+                    .1
+            })
+            .collect::<Vec<_>>();
+
+        assert!((apexes[0].0 - apexes[1].0).abs() < 0.01, "{apexes:?}");
+        assert!(apexes.iter().all(|a| (a.1 - 1.).abs() < 0.05), "{apexes:?}");
+
+        comparison
+            .clone()
+            .save("test-img/comparison_overlay_test.png")
+            .unwrap();
+
+        Comparison {
+            layout: Layout::Stacked,
+            ..comparison
+        }
+        .with_title("R16443 against 0374")
+        .save("test-img/comparison_stacked_test.png")
+        .unwrap();
+
+        assert!(
+            Comparison::new(Layout::Overlay)
+                .with_run(&sample)
+                .unwrap()
+                .with_alignment("R-999")
+                .to_svg()
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,446 @@
+use std::{error::Error, path::Path};
+
+use nalgebra::{DMatrix, DVector};
+use plotters::{coord::Shift, prelude::*};
+
+use crate::{
+    io::Detector,
+    peak_detection::{DDOGPeakDetector, Peak},
+};
+
+use super::{Figure, Theme, draw_mesh};
+
+/// Response of a signal to the [`DDOGPeakDetector`] wavelet as a heatmap of scale against
+/// retention time, for tuning the detector scales.
+///
+/// Every ridge point, i.e. a response minimum below the detection threshold, is marked in the
+/// colour of the group it ends up in, and the peak each group is combined into is marked on the
+/// signal above the heatmap.
+#[derive(Debug, Clone)]
+pub struct Scalogram<'a> {
+    signal: &'a DVector<f64>,
+    values_per_second: f64,
+    scales: Vec<f64>,
+    responses: DMatrix<f64>,
+    groups: Vec<Vec<Peak>>,
+    window: Option<(f64, f64)>,
+    title: String,
+    size: (u32, u32),
+    theme: Theme,
+}
+
+/// Heatmap cells of a window, with the scales sorted ascending.
+struct Grid {
+    scales: Vec<f64>,
+    times: Vec<f64>,
+    /// Response relative to the strongest one of the scale in the window, one row per scale.
+    /// Peaks are close to 1, everything that is not a minimum is 0.
+    intensities: Vec<Vec<f64>>,
+}
+
+impl<'a> Scalogram<'a> {
+    /// Runs `peak_detector` on `signal`, keeping its intermediate results.
+    pub fn new(
+        peak_detector: &DDOGPeakDetector,
+        signal: &'a DVector<f64>,
+        values_per_second: f64,
+    ) -> Self {
+        let responses = peak_detector.responses(signal);
+        let ridge_points = peak_detector.ridge_points(signal, &responses);
+
+        Self {
+            signal,
+            values_per_second,
+            scales: peak_detector.scales().to_vec(),
+            responses,
+            groups: DDOGPeakDetector::group(ridge_points),
+            window: None,
+            title: String::new(),
+            size: (1600, 900),
+            theme: Theme::default(),
+        }
+    }
+
+    pub fn from_detector(peak_detector: &DDOGPeakDetector, detector: &'a Detector) -> Self {
+        Self::new(peak_detector, &detector.values, detector.values_per_second)
+    }
+
+    /// Only plots from `start` to `end` seconds.
+    pub fn with_window(mut self, start: f64, end: f64) -> Self {
+        self.window = Some((start.min(end), start.max(end)));
+        self
+    }
+
+    pub fn with_title(mut self, title: &str) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Size in pixels.
+    pub fn with_size(mut self, width: u32, height: u32) -> Self {
+        self.size = (width, height);
+        self
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Ridge points grouped the way [`DDOGPeakDetector`] groups them.
+    pub fn groups(&self) -> &[Vec<Peak>] {
+        &self.groups
+    }
+
+    /// The peaks the detector reports, one per group.
+    pub fn peaks(&self) -> Vec<Peak> {
+        self.groups
+            .iter()
+            .map(|group| DDOGPeakDetector::combine(group))
+            .collect()
+    }
+
+    fn time(&self, index: f64) -> f64 {
+        index / self.values_per_second
+    }
+
+    fn window(&self) -> (f64, f64) {
+        self.window
+            .unwrap_or((0., self.time(self.signal.len() as f64)))
+    }
+
+    /// Sample indices within the window.
+    fn indices(&self, (start, end): (f64, f64)) -> (usize, usize) {
+        let index = |t: f64| ((t * self.values_per_second).max(0.) as usize).min(self.signal.len());
+
+        (index(start), index(end).max(index(start)))
+    }
+
+    /// Samples the window into at most `columns` columns, keeping the strongest response of
+    /// every column so that narrow ridges don't disappear.
+    fn grid(&self, window: (f64, f64), columns: usize) -> Grid {
+        let (first, last) = self.indices(window);
+        let columns = columns.clamp(1, (last - first).max(1));
+        let bin = (last - first) as f64 / columns as f64;
+        let bounds = |c: usize| {
+            let start = first + (c as f64 * bin) as usize;
+            (
+                start,
+                (first + ((c + 1) as f64 * bin) as usize).max(start + 1),
+            )
+        };
+
+        let mut order = (0..self.scales.len()).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| self.scales[a].total_cmp(&self.scales[b]));
+
+        let intensities = order
+            .iter()
+            .map(|&row| {
+                let row = self.responses.row(row);
+                let cells = (0..columns)
+                    .map(|c| {
+                        let (start, end) = bounds(c);
+                        (start..end.min(row.len()))
+                            .map(|i| row[i])
+                            .fold(0., f64::min)
+                    })
+                    .collect::<Vec<_>>();
+                let strongest = cells.iter().copied().fold(0., f64::min);
+
+                cells
+                    .iter()
+                    .map(|v| if strongest < 0. { v / strongest } else { 0. })
+                    .collect()
+            })
+            .collect();
+
+        Grid {
+            scales: order.iter().map(|&row| self.scales[row]).collect(),
+            times: (0..columns)
+                .map(|c| self.time(bounds(c).0 as f64))
+                .collect(),
+            intensities,
+        }
+    }
+
+    fn draw_signal<DB: DrawingBackend>(
+        &self,
+        area: &DrawingArea<DB, Shift>,
+        window: (f64, f64),
+        peaks: &[(usize, Peak)],
+    ) -> Result<(), Box<dyn Error>>
+    where
+        DB::ErrorType: 'static,
+    {
+        let (first, last) = self.indices(window);
+        let values = self.signal.rows(first, last - first);
+        let (min, max) = values
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| {
+                (lo.min(v), hi.max(v))
+            });
+        let (min, max) = if min < max { (min, max) } else { (0., 1.) };
+        let span = max - min;
+
+        let mut chart = ChartBuilder::on(area)
+            .margin(10)
+            .x_label_area_size(3 * self.theme.font_size)
+            .y_label_area_size(6 * self.theme.font_size)
+            .build_cartesian_2d(window.0..window.1, (min - 0.02 * span)..(max + 0.05 * span))?;
+
+        draw_mesh(&mut chart, &self.theme, "Signal")?;
+
+        chart.draw_series(LineSeries::new(
+            values
+                .iter()
+                .enumerate()
+                .map(|(i, &v)| (self.time((first + i) as f64), v)),
+            self.theme.trace,
+        ))?;
+
+        chart.draw_series(
+            peaks
+                .iter()
+                .filter(|(_, p)| (window.0..=window.1).contains(&self.time(p.pos)))
+                .map(|(group, p)| {
+                    TriangleMarker::new(
+                        (self.time(p.pos), p.height),
+                        6,
+                        Palette99::pick(*group).filled(),
+                    )
+                }),
+        )?;
+
+        Ok(())
+    }
+
+    fn draw_heatmap<DB: DrawingBackend>(
+        &self,
+        area: &DrawingArea<DB, Shift>,
+        window: (f64, f64),
+        peaks: &[(usize, Peak)],
+    ) -> Result<(), Box<dyn Error>>
+    where
+        DB::ErrorType: 'static,
+    {
+        let (width, _) = area.dim_in_pixel();
+        let grid = self.grid(window, width as usize);
+        let Some((&lowest, &highest)) = grid.scales.first().zip(grid.scales.last()) else {
+            return Err("The peak detector has no scales.".into());
+        };
+
+        // every scale covers the range half way to its neighbours
+        let edges = grid
+            .scales
+            .iter()
+            .enumerate()
+            .map(|(i, &scale)| {
+                let below = i.checked_sub(1).map(|j| grid.scales[j]);
+                let above = grid.scales.get(i + 1).copied();
+                let half = |other: Option<f64>| other.map(|o| (o - scale).abs() / 2.);
+                let (down, up) = match (half(below), half(above)) {
+                    (Some(d), Some(u)) => (d, u),
+                    (Some(d), None) => (d, d),
+                    (None, Some(u)) => (u, u),
+                    (None, None) => (0.5, 0.5),
+                };
+
+                (scale - down, scale + up)
+            })
+            .collect::<Vec<_>>();
+        let y_range = edges[0].0.min(lowest)..edges[edges.len() - 1].1.max(highest);
+
+        let mut chart = ChartBuilder::on(area)
+            .margin(10)
+            .x_label_area_size(3 * self.theme.font_size)
+            .y_label_area_size(6 * self.theme.font_size)
+            .build_cartesian_2d(window.0..window.1, y_range)?;
+
+        let column_width = (window.1 - window.0) / grid.times.len() as f64;
+
+        chart.draw_series(grid.intensities.iter().zip(&edges).flat_map(
+            |(row, &(bottom, top))| {
+                row.iter().zip(&grid.times).map(move |(&v, &t)| {
+                    Rectangle::new(
+                        [(t, bottom), (t + column_width, top)],
+                        VulcanoHSL::get_color(v).filled(),
+                    )
+                })
+            },
+        ))?;
+
+        draw_mesh(&mut chart, &self.theme, "Wavelet scale (samples)")?;
+
+        let visible = |p: &Peak| (window.0..=window.1).contains(&self.time(p.pos));
+
+        chart.draw_series(self.groups.iter().enumerate().flat_map(|(g, group)| {
+            group.iter().filter(|p| visible(p)).map(move |p| {
+                Circle::new(
+                    (self.time(p.pos), p.width),
+                    3,
+                    Palette99::pick(g).stroke_width(2),
+                )
+            })
+        }))?;
+
+        chart.draw_series(peaks.iter().filter(|(_, p)| visible(p)).map(|(g, p)| {
+            Cross::new(
+                (self.time(p.pos), p.width),
+                6,
+                Palette99::pick(*g).stroke_width(2),
+            )
+        }))?;
+
+        Ok(())
+    }
+
+    /// Renders the response as a surface turning from the side to the top view into an
+    /// animated GIF, waiting `frame_delay` milliseconds between frames.
+    pub fn animate(
+        &self,
+        path: impl AsRef<Path>,
+        frames: usize,
+        frame_delay: u32,
+    ) -> Result<(), Box<dyn Error>> {
+        let window = self.window();
+        let grid = self.grid(window, 200);
+        let Some((&lowest, &highest)) = grid.scales.first().zip(grid.scales.last()) else {
+            return Err("The peak detector has no scales.".into());
+        };
+
+        let root = BitMapBackend::gif(path, self.size, frame_delay)?.into_drawing_area();
+        let font = (self.theme.font.as_str(), self.theme.font_size)
+            .into_font()
+            .color(&self.theme.text);
+
+        for frame in 0..frames {
+            root.fill(&self.theme.background)?;
+
+            let mut chart = ChartBuilder::on(&root)
+                .caption(
+                    &self.title,
+                    (self.theme.font.as_str(), self.theme.font_size * 3 / 2)
+                        .into_font()
+                        .color(&self.theme.text),
+                )
+                .build_cartesian_3d(lowest..highest, 0.0..1.0, window.0..window.1)?;
+
+            // from the side up to the top view and back
+            let turn = frame as f64 / frames.max(2).saturating_sub(1) as f64;
+            chart.with_projection(|mut p| {
+                p.pitch = std::f64::consts::PI * (0.5 - (0.5 - turn).abs());
+                p.scale = 0.7;
+                p.into_matrix()
+            });
+
+            chart
+                .configure_axes()
+                .label_style(font.clone())
+                .light_grid_style(self.theme.grid.mix(0.4))
+                .max_light_lines(3)
+                .draw()?;
+
+            chart.draw_series(
+                SurfaceSeries::xoz(
+                    grid.scales.iter().copied(),
+                    grid.times.iter().copied(),
+                    |scale, time| {
+                        let row = grid.scales.partition_point(|&s| s < scale);
+                        let column = grid.times.partition_point(|&t| t < time);
+
+                        grid.intensities[row][column]
+                    },
+                )
+                .style_func(&|&v| VulcanoHSL::get_color(v).filled()),
+            )?;
+
+            root.present()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Figure for Scalogram<'_> {
+    fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn draw<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>) -> Result<(), Box<dyn Error>>
+    where
+        DB::ErrorType: 'static,
+    {
+        let window = self.window();
+
+        root.fill(&self.theme.background)?;
+
+        let root = if self.title.is_empty() {
+            root.clone()
+        } else {
+            root.titled(
+                &self.title,
+                (self.theme.font.as_str(), self.theme.font_size * 3 / 2)
+                    .into_font()
+                    .color(&self.theme.text),
+            )?
+        };
+
+        let peaks = self.peaks().into_iter().enumerate().collect::<Vec<_>>();
+        let (_, height) = root.dim_in_pixel();
+        let (signal, heatmap) = root.split_vertically(height / 4);
+
+        self.draw_signal(&signal, window, &peaks)?;
+        self.draw_heatmap(&heatmap, window, &peaks)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{io::read_run, peak_detection::PeakDetector};
+
+    use super::*;
+
+    #[test]
+    fn scalogram() {
+        let run = read_run("../../gc-data/R16443 - Jun 08 2025, 09;24.fusion-data").unwrap();
+        let detector = run.detector().unwrap();
+        let peak_detector = DDOGPeakDetector::new(vec![80., 40., 20., 10., 5.]);
+
+        let scalogram = Scalogram::from_detector(&peak_detector, detector)
+            .with_title("R16443")
+            .with_window(50., 90.);
+
+        // the same peaks the detector finds on its own
+        let detected = peak_detector.detect_peaks(&detector.values);
+        let peaks = scalogram.peaks();
+        assert_eq!(peaks.len(), detected.len());
+        assert!(peaks.iter().zip(&detected).all(|(a, b)| a.pos == b.pos));
+        assert_eq!(
+            scalogram.groups().iter().map(Vec::len).sum::<usize>(),
+            peak_detector
+                .ridge_points(&detector.values, &peak_detector.responses(&detector.values))
+                .len()
+        );
+
+        let grid = scalogram.grid((50., 90.), 400);
+        assert!(grid.scales.is_sorted());
+        assert_eq!(grid.times.len(), 400);
+        assert!(
+            grid.intensities
+                .iter()
+                .flatten()
+                .all(|v| (0. ..=1.).contains(v))
+        );
+
+        scalogram.save("test-img/scalogram_test.png").unwrap();
+        assert!(scalogram.to_svg().unwrap().contains("<svg"));
+
+        let animation = std::env::temp_dir().join("scalogram_test.gif");
+        scalogram
+            .clone()
+            .with_size(640, 360)
+            .animate(&animation, 3, 100)
+            .unwrap();
+        assert!(animation.metadata().unwrap().len() > 0);
+    }
+}
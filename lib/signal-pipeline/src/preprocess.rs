@@ -22,11 +22,18 @@ impl MovingAverage {
 
 impl Smoother for MovingAverage {
     fn smooth(&self, signal: &mut DVector<f64>) {
+        // running sums, so that wide windows cost no more than narrow ones
+        let mut sums = Vec::with_capacity(signal.len() + 1);
+        sums.push(0.);
+        for v in signal.iter() {
+            sums.push(sums[sums.len() - 1] + v);
+        }
+
         for i in 0..signal.len() {
             let l = i.saturating_sub(self.k / 2);
-            let r = (i + self.k / 2).min(signal.len());
+            let r = (i + self.k / 2).min(signal.len() - 1);
 
-            signal[i] = signal.view_range(l..=r, 0).mean();
+            signal[i] = (sums[r + 1] - sums[l]) / (r + 1 - l) as f64;
         }
     }
 }
@@ -50,6 +57,14 @@ mod test {
         nearly_eq(&expected, &res);
     }
 
+    #[test]
+    fn wide_window() {
+        let mut res = DVector::from_column_slice(&[0., 3., 0., 3.]);
+        MovingAverage::new(3).unwrap().smooth(&mut res);
+
+        nearly_eq(&DVector::from_column_slice(&[1.5, 1., 2., 1.5]), &res);
+    }
+
     #[test]
     fn k_0() {
         assert!(MovingAverage::new(0).is_none());
@@ -0,0 +1,201 @@
+use std::collections::VecDeque;
+
+use nalgebra::DVector;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    io::Detector,
+    preprocess::{MovingAverage, Smoother},
+};
+
+/// Smoothing step of a [`Recipe`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum Smoothing {
+    #[default]
+    None,
+    /// See [`MovingAverage`], `window` in samples.
+    MovingAverage { window: usize },
+}
+
+/// Baseline step of a [`Recipe`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum BaselineModel {
+    /// A baseline of zero, the corrected trace is the smoothed signal.
+    None,
+    /// Straight lines between the instrument's baseline points under every integrated peak,
+    /// the signal itself elsewhere, so the corrected trace holds just the integrated areas.
+    #[default]
+    Integration,
+    /// Lowest signal within `window` seconds around every sample, averaged over the same
+    /// window. Follows drift without needing integrated peaks.
+    RollingMinimum { window: f64 },
+}
+
+/// The processing applied to a detector signal, kept with exported traces so that they can be
+/// reproduced.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(default)]
+pub struct Recipe {
+    pub smoothing: Smoothing,
+    pub baseline: BaselineModel,
+}
+
+/// A detector signal after a [`Recipe`], all traces sampled like the detector.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessedTrace {
+    pub values_per_second: f64,
+    pub raw: DVector<f64>,
+    pub baseline: DVector<f64>,
+    /// Smoothed signal minus the baseline.
+    pub corrected: DVector<f64>,
+}
+
+/// Minimum of every `window` wide neighbourhood, with a monotonic queue.
+fn rolling_minimum(signal: &DVector<f64>, window: usize) -> DVector<f64> {
+    let half = window / 2;
+    let mut queue: VecDeque<usize> = VecDeque::new();
+    let mut minima = DVector::zeros(signal.len());
+    let mut next = 0;
+
+    for i in 0..signal.len() {
+        while next < signal.len() && next <= i + half {
+            while queue.back().is_some_and(|&j| signal[j] >= signal[next]) {
+                queue.pop_back();
+            }
+            queue.push_back(next);
+            next += 1;
+        }
+
+        while queue.front().is_some_and(|&j| j + half < i) {
+            queue.pop_front();
+        }
+
+        minima[i] = signal[*queue.front().expect("the queue holds at least sample i")];
+    }
+
+    minima
+}
+
+impl Recipe {
+    pub fn new(smoothing: Smoothing, baseline: BaselineModel) -> Self {
+        Self {
+            smoothing,
+            baseline,
+        }
+    }
+
+    pub fn apply(&self, detector: &Detector) -> Result<ProcessedTrace, String> {
+        let mut smoothed = detector.values.clone();
+
+        match self.smoothing {
+            Smoothing::None => {}
+            Smoothing::MovingAverage { window } => MovingAverage::new(window)
+                .ok_or("The moving average window must be at least one sample.")?
+                .smooth(&mut smoothed),
+        }
+
+        let baseline = match self.baseline {
+            BaselineModel::None => DVector::zeros(smoothed.len()),
+            BaselineModel::Integration => {
+                let mut baseline = smoothed.clone();
+                let index = |t: f64| (t * detector.values_per_second).round() as usize;
+
+                for peak in detector.peaks.iter().filter(|p| p.area > 0.) {
+                    let (start, end) = (index(peak.start), index(peak.end));
+                    if start >= end || end >= baseline.len() {
+                        continue;
+                    }
+
+                    let (left, right) = match peak.baseline {
+                        Some(b) => (b.start, b.end),
+                        None => (smoothed[start], smoothed[end]),
+                    };
+
+                    for i in start..=end {
+                        baseline[i] =
+                            left + (right - left) * (i - start) as f64 / (end - start) as f64;
+                    }
+                }
+
+                baseline
+            }
+            BaselineModel::RollingMinimum { window } => {
+                let samples = (window * detector.values_per_second).round() as usize;
+                if samples == 0 {
+                    return Err("The rolling minimum window must be at least one sample.".into());
+                }
+
+                let mut baseline = rolling_minimum(&smoothed, samples);
+                MovingAverage::new(samples)
+                    .expect("window is at least one sample")
+                    .smooth(&mut baseline);
+
+                baseline
+            }
+        };
+
+        Ok(ProcessedTrace {
+            values_per_second: detector.values_per_second,
+            raw: detector.values.clone(),
+            corrected: &smoothed - &baseline,
+            baseline,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::io::read_run;
+
+    use super::*;
+
+    #[test]
+    fn recipes() {
+        assert_eq!(
+            rolling_minimum(
+                &DVector::from_column_slice(&[3., 1., 4., 1., 5., 9., 2.]),
+                3
+            )
+            .as_slice(),
+            [1., 1., 1., 1., 1., 2., 2.]
+        );
+
+        let run = read_run("../../gc-data/R16443 - Jun 08 2025, 09;24.fusion-data").unwrap();
+        let detector = run.detector().unwrap();
+
+        let trace = Recipe::default().apply(detector).unwrap();
+        // between peaks nothing is left, under them the integrated area
+        let peak = detector.peaks.iter().find(|p| p.area > 0.).unwrap();
+        let gap = ((peak.start - 1.) * detector.values_per_second) as usize;
+        assert_eq!(trace.corrected[gap], 0.);
+        assert_eq!(trace.raw, detector.values);
+        let area = (0..trace.corrected.len())
+            .filter(|&i| (peak.start..=peak.end).contains(&detector.time(i)))
+            .map(|i| trace.corrected[i])
+            .sum::<f64>()
+            / detector.values_per_second;
+        assert!(
+            (area - peak.area).abs() / peak.area < 0.05,
+            "{area} {}",
+            peak.area
+        );
+
+        let drift = Recipe::new(
+            Smoothing::MovingAverage { window: 5 },
+            BaselineModel::RollingMinimum { window: 5. },
+        )
+        .apply(detector)
+        .unwrap();
+        let range = detector.values.min()..=detector.values.max();
+        assert!(drift.baseline.iter().all(|b| range.contains(b)));
+        assert!(drift.corrected.max() > 0.9 * (range.end() - range.start()));
+
+        assert!(
+            Recipe::new(Smoothing::MovingAverage { window: 0 }, BaselineModel::None)
+                .apply(detector)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,179 @@
+use std::{collections::HashMap, fmt::Display, sync::Arc};
+
+use itertools::Itertools;
+use refrigerants::{GCReading, RefrigerantMixture, RefrigerantName};
+
+use crate::{
+    math::{self, Loss, MixtureOptimization},
+    uncertainty::MeasurementUncertainty,
+};
+
+/// Mixtures that explain a reading equally well within measurement uncertainty.
+#[derive(Debug, Clone)]
+pub struct AmbiguityGroup {
+    pub mixtures: Vec<Arc<RefrigerantMixture>>,
+    /// Component that best separates the group's fitted compositions.
+    pub distinguishing_component: RefrigerantName,
+    /// Smallest difference in the fitted fraction of that component between any two members.
+    pub separation: f64,
+    /// Standard deviation the component has to be measured with to tell the members apart.
+    pub required_sigma: f64,
+}
+
+/// Fitted reading of a single candidate, keyed by component.
+fn predict(
+    reading: &GCReading,
+    mixture: &Arc<RefrigerantMixture>,
+    weights: &HashMap<RefrigerantName, f64>,
+) -> Result<HashMap<RefrigerantName, f64>, String> {
+    let fit = MixtureOptimization::new(reading, vec![(mixture.clone(), 0.)])?
+        .decompose(Loss::Squared, weights)?;
+
+    Ok(fit
+        .residuals
+        .into_iter()
+        .map(|(name, residual)| {
+            let observed = reading.get_component(&name).copied().unwrap_or(0.);
+            (name, observed - residual)
+        })
+        .collect())
+}
+
+fn separation(
+    a: &HashMap<RefrigerantName, f64>,
+    b: &HashMap<RefrigerantName, f64>,
+    name: &RefrigerantName,
+) -> f64 {
+    (a.get(name).copied().unwrap_or(0.) - b.get(name).copied().unwrap_or(0.)).abs()
+}
+
+/// Groups the candidates that could have produced `reading` (see [`math::valid_comparison`])
+/// into sets whose fitted compositions differ by less than `z` standard deviations on every
+/// component. Each returned group has at least two members.
+pub fn find_ambiguities(
+    reading: &GCReading,
+    candidates: &[Arc<RefrigerantMixture>],
+    uncertainty: &MeasurementUncertainty,
+    z: f64,
+) -> Result<Vec<AmbiguityGroup>, String> {
+    let weights = uncertainty.weights(reading, candidates.iter().map(AsRef::as_ref));
+
+    let fits = candidates
+        .iter()
+        .filter(|m| math::valid_comparison(reading, m))
+        .map(|m| Ok((m, predict(reading, m, &weights)?)))
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let sigma = |name: &RefrigerantName| uncertainty.reading_sigma(reading, name);
+
+    let indistinguishable = |a: &HashMap<RefrigerantName, f64>,
+                             b: &HashMap<RefrigerantName, f64>| {
+        a.keys()
+            .chain(b.keys())
+            .all(|name| separation(a, b, name) < z * sigma(name))
+    };
+
+    // union-find over candidate indices
+    let mut group: Vec<usize> = (0..fits.len()).collect();
+    fn root(group: &mut [usize], mut i: usize) -> usize {
+        while group[i] != i {
+            group[i] = group[group[i]];
+            i = group[i];
+        }
+        i
+    }
+
+    for (i, j) in (0..fits.len()).tuple_combinations() {
+        if indistinguishable(&fits[i].1, &fits[j].1) {
+            let (ri, rj) = (root(&mut group, i), root(&mut group, j));
+            group[ri] = rj;
+        }
+    }
+
+    let members = (0..fits.len()).into_group_map_by(|&i| root(&mut group, i));
+
+    Ok(members
+        .into_values()
+        .filter(|members| members.len() > 1)
+        .filter_map(|members| {
+            let (distinguishing_component, separation) = fits[members[0]]
+                .1
+                .keys()
+                .map(|name| {
+                    let min_separation = members
+                        .iter()
+                        .tuple_combinations()
+                        .map(|(&i, &j)| separation(&fits[i].1, &fits[j].1, name))
+                        .fold(f64::INFINITY, f64::min);
+
+                    (name.clone(), min_separation)
+                })
+                .max_by(|a, b| (a.1 / sigma(&a.0)).total_cmp(&(b.1 / sigma(&b.0))))?;
+
+            Some(AmbiguityGroup {
+                mixtures: members.iter().map(|&i| fits[i].0.clone()).collect(),
+                distinguishing_component,
+                required_sigma: separation / z,
+                separation,
+            })
+        })
+        .sorted_by(|a, b| a.mixtures[0].identifier().cmp(b.mixtures[0].identifier()))
+        .collect())
+}
+
+impl Display for AmbiguityGroup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} are indistinguishable: {} differs by {:.3}%, needs a sigma of {:.3}% or better",
+            self.mixtures.iter().map(|m| m.identifier()).join(", "),
+            self.distinguishing_component,
+            self.separation * 100.,
+            self.required_sigma * 100.
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::{mixture, name, reading};
+
+    use super::*;
+
+    #[test]
+    fn r401a_and_r401b() {
+        let candidates = [
+            mixture(
+                "r-401a",
+                &[("r-22", 0.53), ("r-152a", 0.13), ("r-124", 0.34)],
+            ),
+            mixture(
+                "r-401b",
+                &[("r-22", 0.61), ("r-152a", 0.11), ("r-124", 0.28)],
+            ),
+            mixture("r-410a", &[("r-32", 0.5), ("r-125", 0.5)]),
+            mixture("r-22", &[("r-22", 1.)]),
+        ];
+
+        let reading = reading(&[("r-22", 0.55), ("r-152a", 0.125), ("r-124", 0.325)]);
+
+        let coarse = MeasurementUncertainty::new(HashMap::new(), 0.15);
+        let groups = find_ambiguities(&reading, &candidates, &coarse, 2.).unwrap();
+
+        assert_eq!(groups.len(), 1, "{:?}", groups);
+        assert_eq!(groups[0].mixtures.len(), 2);
+        assert_eq!(groups[0].distinguishing_component, name("r-22"));
+        assert!(
+            groups[0].required_sigma < coarse.sigma(&name("r-22"), 0.55),
+            "{}",
+            groups[0]
+        );
+
+        let fine = MeasurementUncertainty::new(HashMap::new(), 0.01);
+        assert!(
+            find_ambiguities(&reading, &candidates, &fine, 2.)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
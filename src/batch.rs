@@ -0,0 +1,363 @@
+use std::{
+    fmt::Write as _,
+    fs,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+};
+
+use refrigerants::{
+    ClassificationResult, GCReading, MixtureRegistry, RefrigerantMixture, RefrigerantName,
+};
+use signal_pipeline::io;
+
+use crate::{
+    ambiguity::AmbiguityGroup,
+    config::Config,
+    report::{Report, csv_field},
+    run::{self, Analysis},
+    solver::Backend,
+};
+
+/// Columns of `summary.csv`, one row per run that could be processed.
+pub const SUMMARY_HEADER: &str = "run_id,file,sample,timestamp,serial_number,classification,origin,purity,top_contaminants,ambiguities,warnings";
+
+/// Number of contaminants listed per run in the summary.
+const TOP_CONTAMINANTS: usize = 3;
+
+/// Runs peaks → reading → classification over many run files in parallel.
+///
+/// Every run gets a [`Report`] in `<output>/runs/`, and `<output>/summary.csv` lists one line
+/// per run. A file that fails doesn't stop the others, failures are collected in
+/// [`BatchOutcome::errors`] and `<output>/errors.csv`.
+pub struct Batch<'c> {
+    config: &'c Config,
+    registry: MixtureRegistry,
+    output: PathBuf,
+    backend: Backend,
+    threads: usize,
+}
+
+/// What a batch concluded about one run.
+#[derive(Debug, Clone)]
+pub struct RunSummary {
+    pub file: PathBuf,
+    pub run_id: String,
+    pub sample: String,
+    pub timestamp: String,
+    pub serial_number: String,
+    /// Purest classification of the run.
+    pub classification: ClassificationResult,
+    /// Components that are not part of the classification's origin, largest first.
+    pub contaminants: Vec<(RefrigerantName, f64)>,
+    /// Candidate mixtures the run cannot tell apart, see [`Analysis::ambiguities`].
+    pub ambiguities: Vec<AmbiguityGroup>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BatchError {
+    pub file: PathBuf,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BatchOutcome {
+    /// Sorted by timestamp.
+    pub runs: Vec<RunSummary>,
+    /// In the order of the input files.
+    pub errors: Vec<BatchError>,
+}
+
+impl<'c> Batch<'c> {
+    pub fn new(config: &'c Config, output: impl AsRef<Path>) -> Result<Self, String> {
+        Ok(Self {
+            config,
+            registry: config.registry()?,
+            output: output.as_ref().to_path_buf(),
+            backend: Backend::default(),
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+        })
+    }
+
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Number of worker threads, all available cores by default.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Processes `files` and writes the results. Only failing to write the summary is an error,
+    /// problems with single runs end up in [`BatchOutcome::errors`].
+    pub fn run(&self, files: &[PathBuf]) -> Result<BatchOutcome, String> {
+        let runs_dir = self.output.join("runs");
+        fs::create_dir_all(&runs_dir)
+            .map_err(|e| format!("Could not create {}: {}", runs_dir.display(), e))?;
+
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new(Vec::with_capacity(files.len()));
+
+        thread::scope(|scope| {
+            for _ in 0..self.threads.min(files.len()) {
+                scope.spawn(|| {
+                    while let Some(file) = files.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        // a solver panicking on one odd run should not take the batch down
+                        let result =
+                            panic::catch_unwind(AssertUnwindSafe(|| self.process(file, &runs_dir)))
+                                .unwrap_or_else(|_| Err("Processing panicked.".into()));
+
+                        results
+                            .lock()
+                            .expect("workers don't panic while holding the lock")
+                            .push((file, result));
+                    }
+                });
+            }
+        });
+
+        let mut results = results
+            .into_inner()
+            .expect("workers don't panic while holding the lock");
+        results.sort_by_key(|(file, _)| files.iter().position(|f| f == *file));
+
+        let mut outcome = BatchOutcome::default();
+
+        for (file, result) in results {
+            match result {
+                Ok(summary) => outcome.runs.push(summary),
+                Err(error) => outcome.errors.push(BatchError {
+                    file: file.clone(),
+                    error,
+                }),
+            }
+        }
+
+        outcome.runs.sort_by(|a, b| {
+            a.timestamp
+                .cmp(&b.timestamp)
+                .then_with(|| a.file.cmp(&b.file))
+        });
+
+        write(&self.output.join("summary.csv"), &outcome.summary_csv())?;
+        write(&self.output.join("errors.csv"), &outcome.errors_csv())?;
+
+        Ok(outcome)
+    }
+
+    /// Processes a single run file and writes its report into `runs_dir`.
+    pub fn process(&self, file: &Path, runs_dir: &Path) -> Result<RunSummary, String> {
+        let run = io::load_run(file, &self.config.text_format).map_err(|e| e.to_string())?;
+        let detector = run.detector().map_err(|e| e.to_string())?;
+
+        let Analysis {
+            reading,
+            classifications,
+            usage,
+            ambiguities,
+            mut warnings,
+        } = run::analyze(detector, self.config, &self.registry, self.backend)?;
+        let best = classifications[0].clone();
+
+        let origin = self
+            .registry
+            .by_name(&best.origin)
+            .expect("classified against the registry");
+
+        let stem = file
+            .file_stem()
+            .map_or(run.id.clone(), |s| s.to_string_lossy().to_string());
+
+        let mut report = Report::new(&stem, &self.config.version(), &reading);
+
+        for classification in &classifications {
+            report = report.with_classification(classification);
+        }
+
+        match usage {
+            Ok(usage) => report = report.with_optimization("usage", self.backend, &usage),
+            Err(e) => warnings.push(format!("Usage optimization failed: {}", e)),
+        }
+
+        for group in &ambiguities {
+            report = report.with_ambiguity(group);
+        }
+
+        write(&runs_dir.join(format!("{stem}.json")), &report.to_json()?)?;
+
+        Ok(RunSummary {
+            file: file.to_path_buf(),
+            run_id: run.id.clone(),
+            sample: run.name.clone(),
+            timestamp: run.timestamp.clone(),
+            serial_number: run.serial_number.clone(),
+            contaminants: contaminants(&reading, origin),
+            classification: best,
+            ambiguities,
+            warnings,
+        })
+    }
+}
+
+fn write(path: &Path, contents: &str) -> Result<(), String> {
+    fs::write(path, contents).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+}
+
+/// Detected components of `reading` that are not part of `origin`, largest first.
+fn contaminants(reading: &GCReading, origin: &RefrigerantMixture) -> Vec<(RefrigerantName, f64)> {
+    let own = origin.component_set();
+
+    let mut contaminants = reading
+        .components()
+        .filter(|(name, v)| **v > 0. && !own.contains(name))
+        .map(|(name, v)| (name.clone(), *v))
+        .collect::<Vec<_>>();
+
+    contaminants.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    contaminants
+}
+
+impl BatchOutcome {
+    pub fn summary_csv(&self) -> String {
+        let mut csv = format!("{SUMMARY_HEADER}\n");
+
+        for run in &self.runs {
+            let contaminants = run
+                .contaminants
+                .iter()
+                .take(TOP_CONTAMINANTS)
+                .map(|(name, v)| format!("{} {:.3}%", name, v * 100.))
+                .collect::<Vec<_>>()
+                .join("; ");
+
+            writeln!(
+                csv,
+                "{},{},{},{},{},{},{},{},{},{},{}",
+                csv_field(&run.run_id),
+                csv_field(&run.file.to_string_lossy()),
+                csv_field(&run.sample),
+                csv_field(&run.timestamp),
+                csv_field(&run.serial_number),
+                csv_field(&run.classification.label),
+                csv_field(run.classification.origin.as_ref()),
+                run.classification.purity,
+                csv_field(&contaminants),
+                csv_field(
+                    &run.ambiguities
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join("; ")
+                ),
+                csv_field(&run.warnings.join("; "))
+            )
+            .expect("writing to a String cannot fail");
+        }
+
+        csv
+    }
+
+    pub fn errors_csv(&self) -> String {
+        let mut csv = String::from("file,error\n");
+
+        for error in &self.errors {
+            writeln!(
+                csv,
+                "{},{}",
+                csv_field(&error.file.to_string_lossy()),
+                csv_field(&error.error)
+            )
+            .expect("writing to a String cannot fail");
+        }
+
+        csv
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use signal_pipeline::io::PEAK_TABLE_EXTENSIONS;
+
+    use crate::catalog::run_files;
+
+    use super::*;
+
+    #[test]
+    fn batch_keeps_going_on_errors() {
+        let config = Config::load("config.json").unwrap();
+        let output = std::env::temp_dir().join(format!("gc-batch-test-{}", std::process::id()));
+
+        let files = [
+            PathBuf::from("gc-data/R16443 - Jun 08 2025, 09;24.fusion-data"),
+            PathBuf::from("gc-data/NOTAFILE.fusion-data"),
+            PathBuf::from("gc-data/0374 - Jun 08 2025, 08;24.fusion-data"),
+        ];
+
+        let outcome = Batch::new(&config, &output)
+            .unwrap()
+            .with_threads(2)
+            .run(&files)
+            .unwrap();
+
+        assert_eq!(outcome.runs.len(), 2);
+        assert_eq!(outcome.errors.len(), 1);
+        assert_eq!(outcome.errors[0].file, files[1]);
+        assert!(outcome.runs[0].timestamp <= outcome.runs[1].timestamp);
+
+        let summary = fs::read_to_string(output.join("summary.csv")).unwrap();
+        assert_eq!(summary.lines().count(), 3);
+        assert!(summary.starts_with(SUMMARY_HEADER));
+
+        let report =
+            fs::read_to_string(output.join("runs").join("R16443 - Jun 08 2025, 09;24.json"))
+                .unwrap();
+        assert!(
+            !Report::from_json(&report)
+                .unwrap()
+                .classifications
+                .is_empty()
+        );
+
+        fs::remove_dir_all(output).unwrap();
+    }
+
+    #[test]
+    fn text_runs_have_no_peak_table() {
+        let config = Config::load("config.json").unwrap();
+        let dir = std::env::temp_dir().join(format!("gc-batch-text-{}", std::process::id()));
+        let output = dir.join("results");
+        fs::create_dir_all(&dir).unwrap();
+
+        let fusion = dir.join("R16443 - Jun 08 2025, 09;24.fusion-data");
+        fs::copy("gc-data/R16443 - Jun 08 2025, 09;24.fusion-data", &fusion).unwrap();
+        let csv = dir.join("R16443.csv");
+        fs::write(&csv, "time,signal\n0,1\n0.1,5\n0.2,1\n").unwrap();
+        fs::write(dir.join("notes.txt"), "cylinder 7 was dented\n").unwrap();
+
+        let files = run_files(&dir, &PEAK_TABLE_EXTENSIONS).unwrap();
+        assert_eq!(files, std::slice::from_ref(&fusion));
+
+        // named explicitly, a text run fails on its own without stopping the batch
+        let outcome = Batch::new(&config, &output)
+            .unwrap()
+            .run(&[fusion, csv.clone()])
+            .unwrap();
+        assert_eq!(outcome.runs.len(), 1);
+        assert_eq!(outcome.errors[0].file, csv);
+        assert!(
+            outcome.errors[0].error.contains("no peak table"),
+            "{}",
+            outcome.errors[0].error
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
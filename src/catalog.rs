@@ -0,0 +1,341 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use serde::{Deserialize, Serialize};
+use signal_pipeline::io::{self, RUN_EXTENSIONS, TextFormat};
+
+/// Version of the catalog layout, bumped like [`SCHEMA_VERSION`](crate::report::SCHEMA_VERSION).
+/// A catalog of another version is rebuilt instead of refreshed.
+pub const CATALOG_FORMAT_VERSION: u32 = 1;
+
+/// Files in `dir` with one of `extensions`, e.g. [`RUN_EXTENSIONS`], sorted by name.
+pub fn run_files(dir: impl AsRef<Path>, extensions: &[&str]) -> Result<Vec<PathBuf>, String> {
+    let dir = dir.as_ref();
+    let mut files = std::fs::read_dir(dir)
+        .map_err(|e| format!("Could not read {}: {}", dir.display(), e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| extensions.iter().any(|e| ext.eq_ignore_ascii_case(e)))
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+
+    Ok(files)
+}
+
+/// The metadata of one run file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CatalogEntry {
+    /// File name within [`Catalog::directory`].
+    pub file: String,
+    /// Size in bytes when the file was read.
+    pub size: u64,
+    /// Modification time in milliseconds since the Unix epoch when the file was read.
+    pub modified: u64,
+    pub id: String,
+    /// Sample name entered on the instrument.
+    pub sample: String,
+    /// Start of the run in RFC 3339, empty if the file doesn't record it.
+    pub timestamp: String,
+    pub method: String,
+    pub serial_number: String,
+    pub tags: Vec<String>,
+    pub detectors: Vec<String>,
+    /// Length of the longest signal in seconds.
+    pub duration: f64,
+}
+
+/// An index of the runs in a directory, kept as JSON next to wherever it is saved and refreshed
+/// by reading only the files that changed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Catalog {
+    pub format_version: u32,
+    pub directory: PathBuf,
+    /// Sorted by timestamp, then file name.
+    pub entries: Vec<CatalogEntry>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CatalogError {
+    pub file: PathBuf,
+    pub error: String,
+}
+
+/// What [`Catalog::refresh`] changed.
+#[derive(Debug, Clone, Default)]
+pub struct RefreshOutcome {
+    /// Files read because they are new or changed.
+    pub read: usize,
+    /// Files taken over unchanged from the previous catalog.
+    pub kept: usize,
+    /// Entries of files that are gone.
+    pub removed: usize,
+    /// Files that could not be read, in order. They are left out of the catalog.
+    pub errors: Vec<CatalogError>,
+}
+
+/// Conditions on catalog entries, all of which have to hold. An empty query matches every run.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Query {
+    pub sample: Option<String>,
+    pub serial_number: Option<String>,
+    pub method: Option<String>,
+    pub tag: Option<String>,
+    /// Earliest timestamp, e.g. `2025-06` or `2025-06-08T14:00`.
+    pub from: Option<String>,
+    /// Latest timestamp, inclusive at the given precision, so `2025-06` includes all of June.
+    pub to: Option<String>,
+}
+
+/// Size and modification time of `file`, which tell whether it needs to be read again.
+fn stamp(file: &Path) -> Result<(u64, u64), String> {
+    let metadata = std::fs::metadata(file).map_err(|e| e.to_string())?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |since| since.as_millis() as u64);
+
+    Ok((metadata.len(), modified))
+}
+
+fn read_entry(file: &Path, text_format: &TextFormat) -> Result<CatalogEntry, String> {
+    let (size, modified) = stamp(file)?;
+    let header = io::load_header(file, text_format).map_err(|e| e.to_string())?;
+
+    Ok(CatalogEntry {
+        file: file
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        size,
+        modified,
+        id: header.id,
+        sample: header.name,
+        timestamp: header.timestamp,
+        method: header.method,
+        serial_number: header.serial_number,
+        tags: header.tags,
+        duration: header
+            .detectors
+            .iter()
+            .map(|d| d.len as f64 / d.values_per_second)
+            .fold(0., f64::max),
+        detectors: header.detectors.into_iter().map(|d| d.name).collect(),
+    })
+}
+
+impl Catalog {
+    /// An empty catalog of `directory`, see [`Catalog::refresh`] to fill it.
+    pub fn new(directory: impl AsRef<Path>) -> Self {
+        Self {
+            format_version: CATALOG_FORMAT_VERSION,
+            directory: directory.as_ref().to_path_buf(),
+            entries: vec![],
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path.as_ref())
+            .map_err(|e| format!("Could not read {}: {}", path.as_ref().display(), e))?;
+
+        Self::from_json(&json)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        std::fs::write(path.as_ref(), self.to_json()?)
+            .map_err(|e| format!("Could not write {}: {}", path.as_ref().display(), e))
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+
+    /// Parses a catalog, rejecting catalogs written with another layout.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let catalog: Catalog = serde_json::from_str(json).map_err(|e| e.to_string())?;
+
+        if catalog.format_version != CATALOG_FORMAT_VERSION {
+            return Err(format!(
+                "Catalog format version {} is not the supported version {}.",
+                catalog.format_version, CATALOG_FORMAT_VERSION
+            ));
+        }
+
+        Ok(catalog)
+    }
+
+    /// Brings the catalog up to date with its directory. Files whose size and modification
+    /// time are unchanged keep their entries, new and changed files are read, and entries of
+    /// removed files are dropped.
+    pub fn refresh(&mut self, text_format: &TextFormat) -> Result<RefreshOutcome, String> {
+        let mut previous = self
+            .entries
+            .drain(..)
+            .map(|entry| (entry.file.clone(), entry))
+            .collect::<HashMap<_, _>>();
+        let mut outcome = RefreshOutcome::default();
+
+        for file in run_files(&self.directory, &RUN_EXTENSIONS)? {
+            let name = file
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let unchanged = previous.remove(&name).filter(|entry| {
+                stamp(&file).is_ok_and(|stamp| stamp == (entry.size, entry.modified))
+            });
+
+            match unchanged {
+                Some(entry) => {
+                    self.entries.push(entry);
+                    outcome.kept += 1;
+                }
+                None => match read_entry(&file, text_format) {
+                    Ok(entry) => {
+                        self.entries.push(entry);
+                        outcome.read += 1;
+                    }
+                    Err(error) => outcome.errors.push(CatalogError { file, error }),
+                },
+            }
+        }
+
+        outcome.removed = previous.len();
+        self.entries.sort_by(|a, b| {
+            a.timestamp
+                .cmp(&b.timestamp)
+                .then_with(|| a.file.cmp(&b.file))
+        });
+
+        Ok(outcome)
+    }
+
+    /// Entries matching `query`, in catalog order.
+    pub fn query<'c>(&'c self, query: &Query) -> Vec<&'c CatalogEntry> {
+        self.entries.iter().filter(|e| query.matches(e)).collect()
+    }
+
+    /// Full path of the run file of `entry`.
+    pub fn path(&self, entry: &CatalogEntry) -> PathBuf {
+        self.directory.join(&entry.file)
+    }
+}
+
+impl Query {
+    pub fn with_sample(mut self, sample: &str) -> Self {
+        self.sample = Some(sample.into());
+        self
+    }
+
+    pub fn with_serial_number(mut self, serial_number: &str) -> Self {
+        self.serial_number = Some(serial_number.into());
+        self
+    }
+
+    pub fn with_method(mut self, method: &str) -> Self {
+        self.method = Some(method.into());
+        self
+    }
+
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    /// Runs between `from` and `to`, both inclusive at their precision. Timestamps are compared
+    /// as text, so both should be written like the instrument writes them, in UTC for Fusion.
+    pub fn with_period(mut self, from: Option<&str>, to: Option<&str>) -> Self {
+        self.from = from.map(Into::into);
+        self.to = to.map(Into::into);
+        self
+    }
+
+    pub fn matches(&self, entry: &CatalogEntry) -> bool {
+        let same = |condition: &Option<String>, value: &str| {
+            condition
+                .as_ref()
+                .is_none_or(|c| c.trim().eq_ignore_ascii_case(value.trim()))
+        };
+
+        same(&self.sample, &entry.sample)
+            && same(&self.serial_number, &entry.serial_number)
+            && same(&self.method, &entry.method)
+            && self
+                .tag
+                .as_ref()
+                .is_none_or(|tag| entry.tags.iter().any(|t| same(&Some(tag.clone()), t)))
+            && self.from.as_ref().is_none_or(|from| {
+                !entry.timestamp.is_empty() && entry.timestamp.as_str() >= from.as_str()
+            })
+            && self.to.as_ref().is_none_or(|to| {
+                !entry.timestamp.is_empty()
+                    && entry.timestamp.get(..to.len()).unwrap_or(&entry.timestamp) <= to.as_str()
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn catalog_of_runs() {
+        let dir = std::env::temp_dir().join(format!("gc-catalog-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in [
+            "R16443 - Jun 08 2025, 09;24.fusion-data",
+            "G098075 - Jun 10 2025, 12;50.fusion-data",
+        ] {
+            std::fs::copy(Path::new("gc-data").join(name), dir.join(name)).unwrap();
+        }
+        std::fs::write(dir.join("broken.fusion-data"), "{").unwrap();
+
+        let mut catalog = Catalog::new(&dir);
+        let outcome = catalog.refresh(&TextFormat::default()).unwrap();
+        assert_eq!((outcome.read, outcome.kept), (2, 0));
+        assert_eq!(outcome.errors.len(), 1);
+        assert!(
+            catalog
+                .entries
+                .is_sorted_by(|a, b| a.timestamp <= b.timestamp)
+        );
+
+        let entry = &catalog.entries[0];
+        assert_eq!(entry.sample, "R16443");
+        assert_eq!(entry.serial_number, "70152956");
+        assert_eq!(entry.duration, 140.);
+
+        let all = |query: Query| {
+            catalog
+                .query(&query)
+                .iter()
+                .map(|e| e.sample.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(all(Query::default().with_sample("g098075")), ["G098075"]);
+        assert_eq!(
+            all(Query::default().with_period(Some("2025-06"), Some("2025-06"))).len(),
+            2
+        );
+        assert_eq!(
+            all(Query::default().with_period(None, Some("2025-06-08"))),
+            ["R16443"]
+        );
+        assert!(all(Query::default().with_period(Some("2025-07"), None)).is_empty());
+        assert!(all(Query::default().with_method("Golden").with_tag("nope")).is_empty());
+
+        // only what changed is read again
+        let mut read = Catalog::from_json(&catalog.to_json().unwrap()).unwrap();
+        assert_eq!(read, catalog);
+        std::fs::remove_file(dir.join("G098075 - Jun 10 2025, 12;50.fusion-data")).unwrap();
+        let outcome = read.refresh(&TextFormat::default()).unwrap();
+        assert_eq!((outcome.read, outcome.kept, outcome.removed), (0, 1, 1));
+        assert_eq!(read.entries.len(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,187 @@
+use std::{collections::HashMap, sync::Arc};
+
+use refrigerants::{ClassificationResult, GCReading, RefrigerantMixture, RefrigerantName};
+
+use crate::{
+    math::{self, DEFAULT_LOW_THRESHOLD, MixtureOptimization},
+    solver::Backend,
+};
+
+/// Concentrations below this are solver noise and left out of classification results.
+const MIN_CONCENTRATION: f64 = 1e-6;
+
+/// Classifies `reading` as coming from `origin`.
+///
+/// The reading is explained by `origin` and every other candidate that fits it, preferring
+/// `origin`, and the result is checked against the classifications of `origin`, see
+/// [`RefrigerantMixture::classify`].
+pub fn classify(
+    reading: &GCReading,
+    origin: &Arc<RefrigerantMixture>,
+    candidates: &[Arc<RefrigerantMixture>],
+    backend: Backend,
+) -> Result<ClassificationResult, String> {
+    if !math::valid_comparison(reading, origin) {
+        return Err(format!(
+            "The reading lacks components of {}.",
+            origin.identifier()
+        ));
+    }
+
+    let mixtures = candidates
+        .iter()
+        .filter(|mix| mix.identifier() != origin.identifier())
+        .filter(|mix| math::valid_comparison(reading, mix))
+        .chain([origin])
+        .map(|mix| (mix.clone(), 0.))
+        .collect();
+
+    let (concentrations, _) = MixtureOptimization::new(reading, mixtures)?
+        .with_backend(backend)
+        .optimize_max_refrigerant(origin.identifier())?;
+
+    let components = concentrations
+        .iter()
+        .filter(|(c, _)| *c > MIN_CONCENTRATION)
+        .map(|(c, mix)| (mix.identifier().clone(), *c))
+        .collect::<HashMap<_, _>>();
+
+    let purity = components.get(origin.identifier()).copied().unwrap_or(0.);
+    let max_low = math::find_max_low(reading, origin, DEFAULT_LOW_THRESHOLD);
+
+    Ok(origin.classify(purity, max_low, components))
+}
+
+/// Classifies `reading` as every candidate it could come from, purest first, with a warning
+/// for each origin the classification failed for. Only failing for every origin is an error.
+pub fn classify_reading(
+    reading: &GCReading,
+    candidates: &[Arc<RefrigerantMixture>],
+    backend: Backend,
+) -> Result<(Vec<ClassificationResult>, Vec<String>), String> {
+    let origins = candidates
+        .iter()
+        .filter(|mix| math::valid_comparison(reading, mix))
+        .collect::<Vec<_>>();
+
+    if origins.is_empty() {
+        return Err("No configured mixture fits the reading.".into());
+    }
+
+    rank(origins.into_iter().map(|origin| {
+        (
+            origin.identifier(),
+            classify(reading, origin, candidates, backend),
+        )
+    }))
+}
+
+/// Sorts the classifications of each origin purest first, turning failed ones into warnings.
+fn rank<'a>(
+    outcomes: impl IntoIterator<Item = (&'a RefrigerantName, Result<ClassificationResult, String>)>,
+) -> Result<(Vec<ClassificationResult>, Vec<String>), String> {
+    let mut results = vec![];
+    let mut warnings = vec![];
+
+    for (origin, outcome) in outcomes {
+        match outcome {
+            Ok(result) => results.push(result),
+            Err(e) => warnings.push(format!("Could not classify as {}: {}", origin, e)),
+        }
+    }
+
+    if results.is_empty() {
+        return Err(warnings.join("; "));
+    }
+
+    results.sort_by(|a, b| {
+        b.purity
+            .total_cmp(&a.purity)
+            .then_with(|| a.origin.cmp(&b.origin))
+    });
+
+    Ok((results, warnings))
+}
+
+#[cfg(test)]
+mod test {
+    use refrigerants::MixtureRegistry;
+
+    use crate::test_util::{name, reading};
+
+    use super::*;
+
+    #[test]
+    fn classify_against_registry() {
+        let mut registry = MixtureRegistry::try_from(vec![
+            serde_json::from_str(
+                r#"{
+                    "identifier": "r-410a",
+                    "components": { "r-32": 0.5, "r-125": 0.5 },
+                    "classifications": {
+                        "R-410aM": { "purity": 0.98, "max_lows": 0.005 },
+                        "Mix 410a": { "purity": 0.5 }
+                    }
+                }"#,
+            )
+            .unwrap(),
+        ])
+        .unwrap();
+        registry.insert_pure(&[name("r-32"), name("r-125"), name("r-22")]);
+
+        let contaminated = reading(&[("r-32", 0.495), ("r-125", 0.495), ("r-22", 0.01)]);
+
+        let (results, warnings) =
+            classify_reading(&contaminated, registry.mixtures(), Backend::default()).unwrap();
+        let best = &results[0];
+
+        assert!(warnings.is_empty());
+        assert_eq!(best.origin, name("r-410a"));
+        assert!((best.purity - 0.99).abs() < 1e-4, "{best}");
+        // 1% R-22 is above the 0.5% allowed lows of R-410aM
+        assert_eq!(best.label, "Mix 410a");
+        assert!(results.iter().any(|r| r.origin == name("r-22")));
+
+        let r22 = registry.by_name(&name("r-22")).unwrap();
+        let pure = reading(&[("r-22", 1.)]);
+        assert_eq!(
+            classify(&pure, r22, registry.mixtures(), Backend::default())
+                .unwrap()
+                .label,
+            "R-22"
+        );
+        assert!(
+            classify(
+                &pure,
+                registry.mixtures().first().unwrap(),
+                &[],
+                Backend::Nnls
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn failed_origins_become_warnings() {
+        let r22 = name("r-22");
+        let r32 = name("r-32");
+        let result = ClassificationResult {
+            label: "R-32".into(),
+            origin: r32.clone(),
+            purity: 1.,
+            components: HashMap::from([(r32.clone(), 1.)]),
+        };
+
+        let (results, warnings) = rank([
+            (&r22, Err("Solver failed.".to_string())),
+            (&r32, Ok(result)),
+        ])
+        .unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].origin, r32);
+        assert_eq!(warnings, vec!["Could not classify as R-22: Solver failed."]);
+
+        assert!(rank([(&r22, Err("Solver failed.".to_string()))]).is_err());
+    }
+}
@@ -0,0 +1,212 @@
+use std::fmt::Display;
+
+use refrigerants::{Detection, GCReading, RefrigerantMixture, RefrigerantName};
+
+/// How one component of the target mixture compares to the reading.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentFit {
+    pub name: RefrigerantName,
+    /// Fraction of the component in the target mixture, in the reading's basis.
+    pub nominal: f64,
+    /// Reported fraction, 0 if the component is missing from the reading.
+    pub observed: f64,
+    pub detection: Detection,
+    /// Highest concentration of the mixture this component allows, `observed / nominal`, or
+    /// the limit of detection over `nominal` if it was not detected.
+    pub bound: f64,
+    /// Observed minus the fraction expected at the estimated concentration. Positive when the
+    /// component over-shoots, e.g. because something else contributes it too.
+    pub excess: f64,
+}
+
+/// Concentration of a mixture in a reading, limited by its scarcest component.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConcentrationEstimate {
+    pub mixture: RefrigerantName,
+    /// Fraction of the sample that can be the mixture, at most 1.
+    pub concentration: f64,
+    pub limiting_component: RefrigerantName,
+    /// Sorted by name.
+    pub components: Vec<ComponentFit>,
+}
+
+impl ConcentrationEstimate {
+    pub fn limiting(&self) -> &ComponentFit {
+        self.components
+            .iter()
+            .find(|c| c.name == self.limiting_component)
+            .expect("limiting component is one of the components")
+    }
+
+    /// `false` if a component of the mixture was not detected.
+    pub fn fully_detected(&self) -> bool {
+        self.components
+            .iter()
+            .all(|c| c.detection != Detection::NotDetected)
+    }
+}
+
+/// Estimates how much of `target` is in `observed` as the largest concentration none of its
+/// components exceed.
+///
+/// Components that were not detected are treated as zero, but still allow up to their limit of
+/// detection since they could be present just below it. `detection_limit` is used for
+/// components the reading has no limits for; pass 0 to treat those as proof the mixture is
+/// absent.
+pub fn estimate(
+    observed: &GCReading,
+    target: &RefrigerantMixture,
+    detection_limit: f64,
+) -> Result<ConcentrationEstimate, String> {
+    if detection_limit.is_nan() || detection_limit < 0. {
+        return Err("Detection limit must not be negative.".into());
+    }
+
+    let nominal = target.fractions(observed.basis())?;
+
+    let mut components = nominal
+        .into_iter()
+        .filter(|(_, fraction)| *fraction > 0.)
+        .map(|(name, nominal)| {
+            let value = observed.get_component(&name).copied().unwrap_or(0.);
+            let detection = observed.detection(&name);
+
+            let upper_bound = match (detection, observed.limits(&name)) {
+                (Detection::NotDetected, None) => value.max(detection_limit),
+                _ => observed.upper_bound(&name),
+            };
+
+            ComponentFit {
+                observed: value,
+                bound: upper_bound / nominal,
+                detection,
+                name,
+                nominal,
+                excess: 0.,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    components.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let limiting = components
+        .iter()
+        .min_by(|a, b| a.bound.total_cmp(&b.bound))
+        .ok_or(format!("{} has no components.", target.identifier()))?;
+
+    let concentration = limiting.bound.min(1.);
+    let limiting_component = limiting.name.clone();
+
+    components
+        .iter_mut()
+        .for_each(|c| c.excess = c.observed - concentration * c.nominal);
+
+    Ok(ConcentrationEstimate {
+        mixture: target.identifier().clone(),
+        concentration,
+        limiting_component,
+        components,
+    })
+}
+
+impl Display for ConcentrationEstimate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at {:.3}%, limited by {}",
+            self.mixture,
+            self.concentration * 100.,
+            self.limiting_component
+        )?;
+
+        if self.limiting().detection != Detection::Quantified {
+            write!(f, " ({})", self.limiting().detection)?;
+        }
+
+        for c in &self.components {
+            write!(
+                f,
+                "\n  {}: nominal {:.3}%, observed {:.3}% ({}), {:+.3}%",
+                c.name,
+                c.nominal * 100.,
+                c.observed * 100.,
+                c.detection,
+                c.excess * 100.
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::HashMap, sync::Arc};
+
+    use refrigerants::DetectionLimits;
+
+    use crate::test_util::{mixture, name, reading};
+
+    use super::*;
+
+    fn r404a() -> Arc<RefrigerantMixture> {
+        mixture(
+            "r-404a",
+            &[("r-125", 0.44), ("r-143a", 0.52), ("r-134a", 0.04)],
+        )
+    }
+
+    #[test]
+    fn limiting_component_and_excess() {
+        let estimate = estimate(
+            &reading(&[
+                ("r-125", 0.45),
+                ("r-143a", 0.468),
+                ("r-134a", 0.04),
+                ("r-22", 0.042),
+            ]),
+            &r404a(),
+            0.,
+        )
+        .unwrap();
+
+        assert!((estimate.concentration - 0.9).abs() < 1e-9, "{estimate}");
+        assert_eq!(estimate.limiting_component, name("r-143a"));
+        assert!(estimate.fully_detected());
+
+        let excess = |n: &str| {
+            estimate
+                .components
+                .iter()
+                .find(|c| c.name == name(n))
+                .unwrap()
+                .excess
+        };
+
+        assert!(excess("r-143a").abs() < 1e-9);
+        assert!((excess("r-125") - (0.45 - 0.9 * 0.44)).abs() < 1e-9);
+        assert!((excess("r-134a") - 0.004).abs() < 1e-9);
+    }
+
+    #[test]
+    fn missing_component_uses_detection_limit() {
+        let partial = reading(&[("r-125", 0.44), ("r-143a", 0.52)]);
+
+        let strict = estimate(&partial, &r404a(), 0.).unwrap();
+        assert_eq!(strict.concentration, 0.);
+        assert_eq!(strict.limiting_component, name("r-134a"));
+
+        let lenient = estimate(&partial, &r404a(), 0.001).unwrap();
+        assert!((lenient.concentration - 0.025).abs() < 1e-9);
+        assert!(!lenient.fully_detected());
+        assert_eq!(lenient.limiting().detection, Detection::NotDetected);
+
+        // limits carried by the reading take precedence
+        let limited = partial.with_limits(HashMap::from([(
+            name("r-134a"),
+            DetectionLimits::new(0.0002, 0.0006).unwrap(),
+        )]));
+        let estimate = estimate(&limited, &r404a(), 0.001).unwrap();
+        assert!((estimate.concentration - 0.005).abs() < 1e-9);
+    }
+}
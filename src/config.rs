@@ -0,0 +1,157 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+use refrigerants::{
+    AliasTable, MixtureRegistry, RefrigerantMixture, RefrigerantName, spec::Specification,
+};
+use serde::Deserialize;
+use signal_pipeline::io::TextFormat;
+
+use crate::uncertainty::MeasurementUncertainty;
+
+/// Contents of `config.json`.
+#[derive(Deserialize, Debug)]
+pub struct Config {
+    /// Declared version of the config, bumped by hand when mixtures or limits change.
+    #[serde(default)]
+    pub version: Option<String>,
+    pub pure_refrigerants: HashSet<RefrigerantName>,
+    pub mixtures: Vec<RefrigerantMixture>,
+    #[serde(default)]
+    pub aliases: AliasTable,
+    #[serde(default)]
+    pub specifications: Vec<Specification>,
+    /// Relative molar response factors of the detector, see [`GCReading::calibrate`].
+    ///
+    /// [`GCReading::calibrate`]: refrigerants::GCReading::calibrate
+    #[serde(default)]
+    pub response_factors: HashMap<RefrigerantName, f64>,
+    /// Layout of delimited text runs, see [`load_run`].
+    ///
+    /// [`load_run`]: signal_pipeline::io::load_run
+    #[serde(default)]
+    pub text_format: TextFormat,
+    #[serde(default)]
+    pub uncertainty: Uncertainty,
+    #[serde(skip)]
+    fingerprint: u64,
+}
+
+/// How precisely the GC measures, used to flag mixtures a reading cannot tell apart, see
+/// [`find_ambiguities`](crate::ambiguity::find_ambiguities).
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Uncertainty {
+    /// Standard deviations of single components, as fractions of the reading.
+    pub sigma: HashMap<RefrigerantName, f64>,
+    /// Relative standard deviation of the other components.
+    pub relative: f64,
+    /// Standard deviations by which fitted compositions have to differ to be told apart.
+    pub z: f64,
+}
+
+impl Default for Uncertainty {
+    fn default() -> Self {
+        Self {
+            sigma: HashMap::new(),
+            relative: 0.02,
+            z: 2.,
+        }
+    }
+}
+
+impl Uncertainty {
+    pub fn measurement(&self) -> MeasurementUncertainty {
+        MeasurementUncertainty::new(self.sigma.clone(), self.relative)
+    }
+}
+
+/// 64 bit FNV-1a, stable across Rust versions unlike the std hashers.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+impl Config {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path.as_ref())
+            .map_err(|e| format!("Could not read {}: {}", path.as_ref().display(), e))?;
+
+        Self::from_json(&json)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let mut config: Config = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        config.fingerprint = fnv1a(json.as_bytes());
+        config.sanity_check()?;
+
+        Ok(config)
+    }
+
+    fn sanity_check(&self) -> Result<(), String> {
+        match self
+            .mixtures
+            .iter()
+            .find(|mix| (mix.components().map(|(_, v)| v).sum::<f64>() - 1.).abs() > 1e-9)
+        {
+            Some(mix) => Err(format!(
+                "Mixture [{}]'s components do not add up to 1.0. (Make sure you are listing them with proportions instead of percentages!)",
+                mix.identifier()
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// The declared version followed by a fingerprint of the file, so that edits without a
+    /// version bump still show up in reports.
+    pub fn version(&self) -> String {
+        format!(
+            "{}+{:016x}",
+            self.version.as_deref().unwrap_or("unversioned"),
+            self.fingerprint
+        )
+    }
+
+    /// All mixtures, plus a pure mixture for every pure refrigerant without one.
+    pub fn registry(&self) -> Result<MixtureRegistry, String> {
+        let mut registry = MixtureRegistry::try_from(self.mixtures.clone())?;
+        registry.insert_pure(&self.pure_refrigerants);
+
+        Ok(registry)
+    }
+
+    pub fn specification(&self, name: &str) -> Option<&Specification> {
+        self.specifications.iter().find(|s| s.name == name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn load_repository_config() {
+        let config = Config::load("config.json").unwrap();
+        let registry = config.registry().unwrap();
+
+        assert!(registry.by_name(&"R-410A".parse().unwrap()).is_some());
+        assert!(registry.by_name(&"R-22".parse().unwrap()).is_some());
+        assert!(config.specification("AHRI 700").is_some());
+        assert_eq!(
+            config.aliases.resolve("Puron").unwrap(),
+            "R-410A".parse().unwrap()
+        );
+        assert_eq!(config.aliases.resolve_all("R-115/R-143").unwrap().len(), 2);
+
+        let edited = std::fs::read_to_string("config.json")
+            .unwrap()
+            .replace("0.995", "0.99");
+        assert_ne!(
+            Config::from_json(&edited).unwrap().version(),
+            config.version()
+        );
+    }
+}
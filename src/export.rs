@@ -0,0 +1,254 @@
+use std::{fmt::Write, path::Path};
+
+use nalgebra::DVector;
+use serde::{Deserialize, Serialize};
+use signal_pipeline::{
+    io::{Detector, IntegratedPeak, Run},
+    processing::{ProcessedTrace, Recipe},
+};
+
+use crate::report::csv_field;
+
+/// Version of the [`ProcessedRun`] layout, bumped like
+/// [`SCHEMA_VERSION`](crate::report::SCHEMA_VERSION).
+pub const PROCESSED_FORMAT_VERSION: u32 = 1;
+
+/// Columns of [`trace_csv`], times in seconds.
+pub const TRACE_CSV_HEADER: &str = "time,raw,baseline,corrected";
+
+/// Columns of [`peaks_csv`], times in seconds. Empty fields for values the source didn't
+/// report.
+pub const PEAKS_CSV_HEADER: &str =
+    "label,start,top,end,area,height,snr,baseline_start,baseline_end,tailing";
+
+/// One sample per row in the [`TRACE_CSV_HEADER`] layout.
+pub fn trace_csv(trace: &ProcessedTrace) -> String {
+    let mut csv = format!("{TRACE_CSV_HEADER}\n");
+
+    for i in 0..trace.raw.len() {
+        writeln!(
+            csv,
+            "{},{},{},{}",
+            i as f64 / trace.values_per_second,
+            trace.raw[i],
+            trace.baseline[i],
+            trace.corrected[i]
+        )
+        .expect("writing to a String cannot fail");
+    }
+
+    csv
+}
+
+/// The integrated peaks of `detector` in the [`PEAKS_CSV_HEADER`] layout.
+pub fn peaks_csv(detector: &Detector) -> String {
+    let optional = |v: Option<f64>| v.map(|v| v.to_string()).unwrap_or_default();
+    let mut csv = format!("{PEAKS_CSV_HEADER}\n");
+
+    for peak in &detector.peaks {
+        writeln!(
+            csv,
+            "{},{},{},{},{},{},{},{},{},{}",
+            csv_field(peak.label.as_deref().unwrap_or_default()),
+            peak.start,
+            peak.top,
+            peak.end,
+            peak.area,
+            peak.height,
+            optional(peak.snr),
+            optional(peak.baseline.map(|b| b.start)),
+            optional(peak.baseline.map(|b| b.end)),
+            optional(detector.tailing(peak))
+        )
+        .expect("writing to a String cannot fail");
+    }
+
+    csv
+}
+
+/// A peak of [`peaks_json`], the fields of the instrument's integration and the tailing we
+/// computed from it.
+#[derive(Serialize)]
+struct PeakRecord<'a> {
+    #[serde(flatten)]
+    peak: &'a IntegratedPeak,
+    tailing: Option<f64>,
+}
+
+/// The integrated peaks of `detector` as a JSON array.
+pub fn peaks_json(detector: &Detector) -> Result<String, String> {
+    let peaks = detector
+        .peaks
+        .iter()
+        .map(|peak| PeakRecord {
+            peak,
+            tailing: detector.tailing(peak),
+        })
+        .collect::<Vec<_>>();
+
+    serde_json::to_string_pretty(&peaks).map_err(|e| e.to_string())
+}
+
+/// A run after processing, with the [`Recipe`] that produced it so that the traces can be
+/// reproduced or reprocessed from the raw signal.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProcessedRun {
+    pub format_version: u32,
+    pub id: String,
+    pub name: String,
+    pub timestamp: String,
+    pub method: String,
+    pub serial_number: String,
+    pub tags: Vec<String>,
+    pub detector: String,
+    pub recipe: Recipe,
+    pub values_per_second: f64,
+    pub raw: Vec<f64>,
+    pub baseline: Vec<f64>,
+    pub corrected: Vec<f64>,
+    pub peaks: Vec<IntegratedPeak>,
+}
+
+impl ProcessedRun {
+    /// Applies `recipe` to `detector` of `run`.
+    pub fn new(run: &Run, detector: &Detector, recipe: Recipe) -> Result<Self, String> {
+        let trace = recipe.apply(detector)?;
+
+        Ok(Self {
+            format_version: PROCESSED_FORMAT_VERSION,
+            id: run.id.clone(),
+            name: run.name.clone(),
+            timestamp: run.timestamp.clone(),
+            method: run.method.clone(),
+            serial_number: run.serial_number.clone(),
+            tags: run.tags.clone(),
+            detector: detector.name.clone(),
+            recipe,
+            values_per_second: trace.values_per_second,
+            raw: trace.raw.as_slice().to_vec(),
+            baseline: trace.baseline.as_slice().to_vec(),
+            corrected: trace.corrected.as_slice().to_vec(),
+            peaks: detector.peaks.clone(),
+        })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path.as_ref())
+            .map_err(|e| format!("Could not read {}: {}", path.as_ref().display(), e))?;
+
+        Self::from_json(&json)
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string(self).map_err(|e| e.to_string())
+    }
+
+    /// Parses a processed run, rejecting files written with a newer layout or with traces of
+    /// different lengths.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let processed: ProcessedRun = serde_json::from_str(json).map_err(|e| e.to_string())?;
+
+        if processed.format_version > PROCESSED_FORMAT_VERSION {
+            return Err(format!(
+                "Processed run format version {} is newer than the supported version {}.",
+                processed.format_version, PROCESSED_FORMAT_VERSION
+            ));
+        }
+
+        if processed.baseline.len() != processed.raw.len()
+            || processed.corrected.len() != processed.raw.len()
+        {
+            return Err("The traces of the processed run differ in length.".into());
+        }
+
+        Ok(processed)
+    }
+
+    pub fn trace(&self) -> ProcessedTrace {
+        ProcessedTrace {
+            values_per_second: self.values_per_second,
+            raw: DVector::from_column_slice(&self.raw),
+            baseline: DVector::from_column_slice(&self.baseline),
+            corrected: DVector::from_column_slice(&self.corrected),
+        }
+    }
+
+    /// The run before processing, with the raw signal and the instrument's peaks.
+    pub fn run(&self) -> Run {
+        Run {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            timestamp: self.timestamp.clone(),
+            method: self.method.clone(),
+            serial_number: self.serial_number.clone(),
+            tags: self.tags.clone(),
+            detectors: vec![Detector {
+                name: self.detector.clone(),
+                values_per_second: self.values_per_second,
+                values: DVector::from_column_slice(&self.raw),
+                peaks: self.peaks.clone(),
+            }],
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use signal_pipeline::{
+        io::read_run,
+        processing::{BaselineModel, Smoothing},
+    };
+
+    use super::*;
+
+    #[test]
+    fn processed_round_trip() {
+        let run = read_run("gc-data/R16443 - Jun 08 2025, 09;24.fusion-data").unwrap();
+        let detector = run.detector().unwrap();
+        let recipe = Recipe::new(
+            Smoothing::MovingAverage { window: 5 },
+            BaselineModel::Integration,
+        );
+
+        let processed = ProcessedRun::new(&run, detector, recipe).unwrap();
+        let json = processed.to_json().unwrap();
+        let read = ProcessedRun::from_json(&json).unwrap();
+        assert_eq!(read, processed);
+
+        // the raw run comes back, and reprocessing it gives the same traces
+        let again = read.run();
+        let again = again.detector().unwrap();
+        assert_eq!(again.values, detector.values);
+        assert_eq!(again.peaks, detector.peaks);
+        assert_eq!(read.recipe.apply(again).unwrap(), processed.trace());
+
+        let newer = json.replacen(
+            "\"format_version\":1",
+            &format!("\"format_version\":{}", PROCESSED_FORMAT_VERSION + 1),
+            1,
+        );
+        assert!(ProcessedRun::from_json(&newer).is_err());
+    }
+
+    #[test]
+    fn csv_exports() {
+        let run = read_run("gc-data/R16443 - Jun 08 2025, 09;24.fusion-data").unwrap();
+        let detector = run.detector().unwrap();
+
+        let trace = trace_csv(&Recipe::default().apply(detector).unwrap());
+        let lines = trace.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], TRACE_CSV_HEADER);
+        assert_eq!(lines.len(), detector.values.len() + 1);
+        assert!(lines[201].starts_with("1,"));
+
+        let peaks = peaks_csv(detector);
+        assert_eq!(peaks.lines().count(), detector.peaks.len() + 1);
+        // unidentified peaks have an empty label
+        assert!(peaks.lines().skip(1).any(|l| l.starts_with(',')));
+
+        let json: serde_json::Value = serde_json::from_str(&peaks_json(detector).unwrap()).unwrap();
+        let first = &json[0];
+        assert_eq!(first["top"], detector.peaks[0].top);
+        assert!(first.get("tailing").is_some());
+    }
+}
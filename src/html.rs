@@ -0,0 +1,484 @@
+use std::{collections::HashMap, fmt::Write};
+
+use refrigerants::{MixtureRegistry, spec::SpecReport};
+use signal_pipeline::{
+    io::{Detector, Run},
+    plot::{Chromatogram, Figure},
+};
+
+use crate::{config::Config, report::ReadingReport, run::Analysis};
+
+/// Inlined so that the report stays a single file.
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em auto; max-width: 1200px; color: #222; }
+h1 { margin-bottom: 0.2em; }
+h2 { margin-top: 1.6em; border-bottom: 1px solid #ccc; }
+table { border-collapse: collapse; margin: 0.5em 0; }
+th, td { padding: 0.25em 0.8em; border-bottom: 1px solid #e4e4e4; text-align: left; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }
+dl { display: grid; grid-template-columns: max-content auto; gap: 0.2em 1.5em; }
+dt { font-weight: bold; }
+dd { margin: 0; }
+.pass { color: #2e7d32; }
+.fail { color: #c62828; font-weight: bold; }
+.open { color: #8d6e00; }
+figure { margin: 1em 0; }
+figure svg { width: 100%; height: auto; }
+";
+
+/// A self-contained HTML document about one run, meant to be handed out per cylinder.
+///
+/// It holds the run's metadata, the chromatogram as inline SVG, the integrated peaks, the
+/// reading derived from them, the classifications, the mixtures the reading cannot tell apart
+/// and the configured specification checks.
+pub struct HtmlReport<'a> {
+    run: &'a Run,
+    detector: &'a Detector,
+    config: &'a Config,
+    registry: &'a MixtureRegistry,
+    analysis: &'a Analysis,
+    source: Option<String>,
+    measurements: HashMap<String, f64>,
+}
+
+/// Escapes text for use in HTML content and attribute values.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+fn percent(fraction: f64) -> String {
+    format!("{:.3}", fraction * 100.)
+}
+
+impl<'a> HtmlReport<'a> {
+    pub fn new(
+        run: &'a Run,
+        config: &'a Config,
+        registry: &'a MixtureRegistry,
+        analysis: &'a Analysis,
+    ) -> Result<Self, String> {
+        Ok(Self {
+            run,
+            detector: run.detector().map_err(|e| e.to_string())?,
+            config,
+            registry,
+            analysis,
+            source: None,
+            measurements: HashMap::new(),
+        })
+    }
+
+    /// File the run was read from, listed with the metadata.
+    pub fn with_source(mut self, source: &str) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Lab values the GC cannot measure, for the measurement criteria of the specifications.
+    pub fn with_measurements(mut self, measurements: HashMap<String, f64>) -> Self {
+        self.measurements = measurements;
+        self
+    }
+
+    /// Every configured specification evaluated for the purest classification's origin.
+    pub fn spec_checks(&self) -> Vec<Result<SpecReport, String>> {
+        let Some(origin) = self
+            .analysis
+            .classifications
+            .first()
+            .and_then(|c| self.registry.by_name(&c.origin))
+        else {
+            return vec![];
+        };
+
+        self.config
+            .specifications
+            .iter()
+            .map(|spec| spec.evaluate(&self.analysis.reading, origin, &self.measurements))
+            .collect()
+    }
+
+    pub fn render(&self) -> Result<String, String> {
+        let chromatogram = Chromatogram::from_detector(self.detector)
+            .with_title(&format!("{} ({})", self.run.name, self.run.timestamp))
+            .with_size(1200, 600)
+            .to_svg()
+            .map_err(|e| format!("Could not plot the chromatogram: {}", e))?;
+
+        let mut html = String::new();
+        self.write(&mut html, &chromatogram)
+            .expect("writing to a String cannot fail");
+
+        Ok(html)
+    }
+
+    fn write(&self, html: &mut String, chromatogram: &str) -> std::fmt::Result {
+        let title = if self.run.name.is_empty() {
+            &self.run.id
+        } else {
+            &self.run.name
+        };
+
+        writeln!(html, "<!DOCTYPE html>")?;
+        writeln!(html, "<html lang=\"en\">")?;
+        writeln!(html, "<head>")?;
+        writeln!(html, "<meta charset=\"utf-8\">")?;
+        writeln!(html, "<title>{}</title>", escape(title))?;
+        writeln!(html, "<style>{}</style>", STYLE)?;
+        writeln!(html, "</head>")?;
+        writeln!(html, "<body>")?;
+        writeln!(html, "<h1>{}</h1>", escape(title))?;
+
+        self.write_metadata(html)?;
+
+        writeln!(html, "<h2>Chromatogram</h2>")?;
+        writeln!(html, "<figure>{}</figure>", chromatogram)?;
+
+        self.write_peaks(html)?;
+        self.write_reading(html)?;
+        self.write_classifications(html)?;
+        self.write_ambiguities(html)?;
+        self.write_spec_checks(html)?;
+
+        if !self.analysis.warnings.is_empty() {
+            writeln!(html, "<h2>Warnings</h2>")?;
+            writeln!(html, "<ul>")?;
+            for warning in &self.analysis.warnings {
+                writeln!(html, "<li>{}</li>", escape(warning))?;
+            }
+            writeln!(html, "</ul>")?;
+        }
+
+        writeln!(html, "</body>")?;
+        writeln!(html, "</html>")
+    }
+
+    fn write_metadata(&self, html: &mut String) -> std::fmt::Result {
+        let run = self.run;
+        let rows = [
+            ("Sample", run.name.clone()),
+            ("Run started", run.timestamp.clone()),
+            ("Run id", run.id.clone()),
+            ("Instrument serial number", run.serial_number.clone()),
+            ("Method", run.method.clone()),
+            ("Tags", run.tags.join(", ")),
+            ("Detector", self.detector.name.clone()),
+            (
+                "Sampling rate",
+                format!("{} Hz", self.detector.values_per_second),
+            ),
+            ("Source file", self.source.clone().unwrap_or_default()),
+            ("Config version", self.config.version()),
+            (
+                "Generated by",
+                format!("gc-analyzer {}", env!("CARGO_PKG_VERSION")),
+            ),
+        ];
+
+        writeln!(html, "<dl>")?;
+        for (term, value) in rows.iter().filter(|(_, value)| !value.is_empty()) {
+            writeln!(html, "<dt>{}</dt><dd>{}</dd>", term, escape(value))?;
+        }
+        writeln!(html, "</dl>")
+    }
+
+    fn write_peaks(&self, html: &mut String) -> std::fmt::Result {
+        let peaks = self.detector.peaks.iter().filter(|p| p.area > 0.);
+        let total = peaks.clone().map(|p| p.area).sum::<f64>();
+
+        writeln!(html, "<h2>Integrated peaks</h2>")?;
+        writeln!(html, "<table>")?;
+        writeln!(
+            html,
+            "<tr><th>Retention time (s)</th><th>Start (s)</th><th>End (s)</th><th>Area</th>\
+             <th>Area %</th><th>Height</th><th>SNR</th><th>Tailing</th><th>Compound</th></tr>"
+        )?;
+
+        for peak in peaks {
+            let compound = match &peak.label {
+                Some(label) => match self.config.aliases.resolve_all(label) {
+                    Ok(names) => escape(
+                        &names
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>()
+                            .join(" + "),
+                    ),
+                    Err(_) => format!("{} (unassigned)", escape(label)),
+                },
+                None => "unidentified".into(),
+            };
+
+            writeln!(
+                html,
+                "<tr><td class=\"num\">{:.2}</td><td class=\"num\">{:.2}</td>\
+                 <td class=\"num\">{:.2}</td><td class=\"num\">{:.1}</td>\
+                 <td class=\"num\">{}</td><td class=\"num\">{:.1}</td>\
+                 <td class=\"num\">{}</td><td class=\"num\">{}</td><td>{}</td></tr>",
+                peak.top,
+                peak.start,
+                peak.end,
+                peak.area,
+                percent(peak.area / total),
+                peak.height,
+                peak.snr.map_or("-".into(), |snr| format!("{:.1}", snr)),
+                self.detector
+                    .tailing(peak)
+                    .map_or("-".into(), |t| format!("{:.2}", t)),
+                compound
+            )?;
+        }
+
+        writeln!(html, "</table>")
+    }
+
+    fn write_reading(&self, html: &mut String) -> std::fmt::Result {
+        let reading = ReadingReport::from(&self.analysis.reading);
+
+        writeln!(html, "<h2>Reading</h2>")?;
+        writeln!(html, "<table>")?;
+        writeln!(
+            html,
+            "<tr><th>Component</th><th>{} %</th><th>Detection</th></tr>",
+            reading.basis
+        )?;
+
+        for c in &reading.components {
+            writeln!(
+                html,
+                "<tr><td>{}</td><td class=\"num\">{}</td><td>{}</td></tr>",
+                escape(c.name.as_ref()),
+                percent(c.value),
+                escape(&c.detection.to_string())
+            )?;
+        }
+
+        writeln!(html, "</table>")
+    }
+
+    fn write_classifications(&self, html: &mut String) -> std::fmt::Result {
+        writeln!(html, "<h2>Classification</h2>")?;
+        writeln!(html, "<table>")?;
+        writeln!(
+            html,
+            "<tr><th>Origin</th><th>Classification</th><th>Purity %</th><th>Explained by</th></tr>"
+        )?;
+
+        for c in &self.analysis.classifications {
+            let mut mixtures = c.components.iter().collect::<Vec<_>>();
+            mixtures.sort_by(|a, b| b.1.total_cmp(a.1).then_with(|| a.0.cmp(b.0)));
+
+            writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td class=\"num\">{}</td><td>{}</td></tr>",
+                escape(c.origin.as_ref()),
+                escape(&c.label),
+                percent(c.purity),
+                mixtures
+                    .iter()
+                    .map(|(name, v)| format!("{} {}%", escape(name.as_ref()), percent(**v)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )?;
+        }
+
+        writeln!(html, "</table>")?;
+
+        match &self.analysis.usage {
+            Ok((concentrations, total)) => {
+                writeln!(
+                    html,
+                    "<p>Fewest mixtures explaining the reading, {}% of it in total:</p>",
+                    percent(*total)
+                )?;
+                writeln!(html, "<table>")?;
+                writeln!(html, "<tr><th>Mixture</th><th>Share %</th></tr>")?;
+
+                for (c, mix) in concentrations.iter().filter(|(c, _)| *c > 0.) {
+                    writeln!(
+                        html,
+                        "<tr><td>{}</td><td class=\"num\">{}</td></tr>",
+                        escape(mix.identifier().as_ref()),
+                        percent(*c)
+                    )?;
+                }
+
+                writeln!(html, "</table>")
+            }
+            Err(e) => writeln!(
+                html,
+                "<p class=\"fail\">Usage optimization failed: {}</p>",
+                escape(e)
+            ),
+        }
+    }
+
+    fn write_ambiguities(&self, html: &mut String) -> std::fmt::Result {
+        if self.analysis.ambiguities.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(html, "<h2>Ambiguities</h2>")?;
+        writeln!(
+            html,
+            "<p class=\"open\">The reading cannot tell these mixtures apart, the classification \
+             may name the wrong one.</p>"
+        )?;
+        writeln!(html, "<table>")?;
+        writeln!(
+            html,
+            "<tr><th>Mixtures</th><th>Distinguishing component</th><th>Difference %</th>\
+             <th>Required sigma %</th></tr>"
+        )?;
+
+        for group in &self.analysis.ambiguities {
+            writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+                group
+                    .mixtures
+                    .iter()
+                    .map(|m| escape(m.identifier().as_ref()))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                escape(group.distinguishing_component.as_ref()),
+                percent(group.separation),
+                percent(group.required_sigma)
+            )?;
+        }
+
+        writeln!(html, "</table>")
+    }
+
+    fn write_spec_checks(&self, html: &mut String) -> std::fmt::Result {
+        let checks = self.spec_checks();
+
+        if checks.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(html, "<h2>Specification checks</h2>")?;
+
+        for check in checks {
+            let report = match check {
+                Ok(report) => report,
+                Err(e) => {
+                    writeln!(html, "<p class=\"fail\">{}</p>", escape(&e))?;
+                    continue;
+                }
+            };
+
+            let (class, verdict) = match report.passed() {
+                Some(true) => ("pass", "PASS"),
+                Some(false) => ("fail", "FAIL"),
+                None => ("open", "INCOMPLETE"),
+            };
+
+            writeln!(
+                html,
+                "<h3>{} as {}: <span class=\"{}\">{}</span></h3>",
+                escape(&report.specification),
+                escape(report.mixture.as_ref()),
+                class,
+                verdict
+            )?;
+            writeln!(html, "<table>")?;
+            writeln!(
+                html,
+                "<tr><th>Criterion</th><th>Measured</th><th>Limit</th><th>Unit</th><th>Result</th></tr>"
+            )?;
+
+            for r in &report.results {
+                let (class, result) = match r.passed() {
+                    Some(true) => ("pass", "pass"),
+                    Some(false) => ("fail", "fail"),
+                    None => ("open", "not measured"),
+                };
+
+                writeln!(
+                    html,
+                    "<tr><td>{}</td><td class=\"num\">{}</td><td>{}</td><td>{}</td>\
+                     <td class=\"{}\">{}</td></tr>",
+                    escape(&r.label),
+                    r.measured.map_or("-".into(), |m| format!("{:.4}", m)),
+                    escape(&r.limit.to_string()),
+                    escape(&r.unit),
+                    class,
+                    result
+                )?;
+            }
+
+            writeln!(html, "</table>")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use signal_pipeline::io::read_run;
+
+    use crate::{run, solver::Backend};
+
+    use super::*;
+
+    #[test]
+    fn report_of_a_run() {
+        let config = Config::load("config.json").unwrap();
+        let registry = config.registry().unwrap();
+        let run = read_run("gc-data/R16443 - Jun 08 2025, 09;24.fusion-data").unwrap();
+        let analysis = run::analyze(
+            run.detector().unwrap(),
+            &config,
+            &registry,
+            Backend::default(),
+        )
+        .unwrap();
+
+        let report = HtmlReport::new(&run, &config, &registry, &analysis)
+            .unwrap()
+            .with_source("R16443 - Jun 08 2025, 09;24.fusion-data")
+            .with_measurements(HashMap::from([("water".into(), 4.)]));
+        let html = report.render().unwrap();
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<svg"));
+        assert!(html.contains(&escape(&run.serial_number)));
+        assert!(html.contains("AHRI 700"));
+
+        // one row per integrated peak besides the header
+        let peaks = run.detectors[0]
+            .peaks
+            .iter()
+            .filter(|p| p.area > 0.)
+            .count();
+        let tables = html.split("<h2>").collect::<Vec<_>>();
+        let peak_table = tables
+            .iter()
+            .find(|t| t.starts_with("Integrated peaks"))
+            .unwrap();
+        assert_eq!(peak_table.matches("<tr>").count(), peaks + 1);
+
+        let checks = report.spec_checks();
+        assert!(checks.iter().all(Result::is_ok));
+
+        assert_eq!(
+            escape("<a href=\"x\">&</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;"
+        );
+    }
+}
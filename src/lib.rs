@@ -0,0 +1,45 @@
+pub mod ambiguity;
+pub mod batch;
+pub mod catalog;
+pub mod classify;
+pub mod concentration;
+pub mod config;
+pub mod export;
+pub mod html;
+pub mod math;
+pub mod report;
+pub mod run;
+pub mod selection;
+pub mod solver;
+pub mod uncertainty;
+
+/// Shorthands for building readings and mixtures in tests.
+#[cfg(test)]
+pub(crate) mod test_util {
+    use std::sync::Arc;
+
+    use refrigerants::{
+        ClassificationList, CompositionBasis, GCReading, RefrigerantMixture, RefrigerantName,
+    };
+
+    pub(crate) fn name(name: &str) -> RefrigerantName {
+        name.parse().unwrap()
+    }
+
+    /// A mixture without classifications.
+    pub(crate) fn mixture(identifier: &str, components: &[(&str, f64)]) -> Arc<RefrigerantMixture> {
+        Arc::new(RefrigerantMixture::new(
+            name(identifier),
+            components.iter().map(|(n, v)| (name(n), *v)).collect(),
+            ClassificationList::default(),
+        ))
+    }
+
+    /// A reading on a mass basis.
+    pub(crate) fn reading(components: &[(&str, f64)]) -> GCReading {
+        GCReading::new(
+            components.iter().map(|(n, v)| (name(n), *v)).collect(),
+            CompositionBasis::Mass,
+        )
+    }
+}
@@ -1,160 +1,704 @@
-use std::{error::Error, ffi::OsString, path::Path, time::Instant};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    str::FromStr,
+    time::Instant,
+};
+
+use gc_analyzer::{
+    batch::Batch,
+    catalog::{Catalog, Query, run_files},
+    config::Config,
+    export::{ProcessedRun, peaks_csv, peaks_json, trace_csv},
+    html::HtmlReport,
+    run,
+    solver::Backend,
+};
+use signal_pipeline::{
+    io::{PEAK_TABLE_EXTENSIONS, RUN_EXTENSIONS, TextFormat},
+    peak_detection::DDOGPeakDetector,
+    plot::{Chromatogram, Comparison, Figure, Layout, Scalogram, Theme},
+    processing::{BaselineModel, Recipe, Smoothing},
+};
+
+const USAGE: &str = "Usage:
+    gc-analyzer plot [--config FILE] [--format png|svg] [--size WxH] [--theme light|dark] [--zoom START:END]... [DATA_DIR] [IMAGE_DIR]
+    gc-analyzer compare [--config FILE] [--stacked] [--align LABEL] [--normalize LABEL] [--window START:END] [--size WxH] [--theme light|dark] [--out FILE] RUN...
+    gc-analyzer scalogram [--config FILE] [--scales A,B,...|START:END[:STEP]] [--window START:END] [--size WxH] [--theme light|dark] [--animate GIF] [--out FILE] RUN
+    gc-analyzer export [--config FILE] [--recipe FILE] [--smooth N] [--baseline none|integration|rolling-min:SECONDS] [--out DIR] RUN...
+    gc-analyzer report [--config FILE] [--backend NAME] [--measure NAME=VALUE]... [--out FILE] RUN
+    gc-analyzer batch [--config FILE] [--out DIR] [--backend NAME] [--threads N] [DATA_DIR]
+    gc-analyzer catalog [--config FILE] [--out FILE] [DATA_DIR]
+    gc-analyzer query [--catalog FILE] [--sample ID] [--serial NUMBER] [--method NAME] [--tag TAG] [--date PREFIX] [--from DATE] [--to DATE] [--paths]";
+
+const DATA_DIR: &str = "gc-data";
+
+const CONFIG: &str = "config.json";
+
+const CATALOG: &str = "catalog.json";
+
+/// Wavelet scales of the scalogram command, in samples.
+const DEFAULT_SCALES: &str = "5,10,20,40,80";
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    let result = match args.first().map(String::as_str) {
+        Some("plot") => plot(&args[1..]),
+        Some("compare") => compare(&args[1..]),
+        Some("scalogram") => scalogram(&args[1..]),
+        Some("export") => export(&args[1..]),
+        Some("report") => report(&args[1..]),
+        Some("batch") => batch(&args[1..]),
+        Some("catalog") => catalog(&args[1..]),
+        Some("query") => query(&args[1..]),
+        _ => Err(USAGE.into()),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn plot(args: &[String]) -> Result<(), String> {
+    let mut config_path = None;
+    let mut dirs = vec![];
+    let mut format = "png";
+    let mut size = (1600, 900);
+    let mut theme = Theme::light();
+    let mut zooms = vec![];
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .map(String::as_str)
+                .ok_or(format!("{} needs a value.\n{}", arg, USAGE))
+        };
+
+        match arg.as_str() {
+            "--config" => config_path = Some(value()?),
+            "--format" => {
+                format = match value()? {
+                    f @ ("png" | "svg") => f,
+                    other => return Err(format!("Unknown image format {}.", other)),
+                }
+            }
+            "--size" => size = parse_pair(value()?, 'x')?,
+            "--theme" => theme = parse_theme(value()?)?,
+            "--zoom" => zooms.push(parse_pair::<f64>(value()?, ':')?),
+            flag if flag.starts_with("--") => {
+                return Err(format!("Unknown option {}.\n{}", flag, USAGE));
+            }
+            dir => dirs.push(dir),
+        }
+    }
+
+    let data_dir = dirs.first().copied().unwrap_or(DATA_DIR);
+    let image_dir = Path::new(dirs.get(1).copied().unwrap_or("gc-data-img"));
+    std::fs::create_dir_all(image_dir).map_err(|e| e.to_string())?;
+
+    let text_format = text_format(config_path)?;
+
+    let plot_run = |file: &Path| -> Result<PathBuf, String> {
+        let run = signal_pipeline::io::load_run(file, &text_format).map_err(|e| e.to_string())?;
+        let detector = run.detector().map_err(|e| e.to_string())?;
+        let path = image_dir.join(format!(
+            "{}.{}",
+            file.file_stem()
+                .expect("listed files have a name")
+                .to_string_lossy(),
+            format
+        ));
+
+        let mut chromatogram = Chromatogram::from_detector(detector)
+            .with_title(&format!("{} ({})", run.name, run.timestamp))
+            .with_size(size.0, size.1)
+            .with_theme(theme.clone());
+        for &(start, end) in &zooms {
+            chromatogram = chromatogram.with_zoom(start, end);
+        }
+
+        chromatogram
+            .save(&path)
+            .map_err(|e| format!("Could not plot: {}", e))?;
+
+        Ok(path)
+    };
+
+    // like batch, a broken run is reported instead of stopping the others
+    let mut errors = vec![];
+    for file in run_files(data_dir, &RUN_EXTENSIONS)? {
+        let time = Instant::now();
+
+        match plot_run(&file) {
+            Ok(path) => println!("{}: {:?}", path.display(), Instant::now() - time),
+            Err(error) => errors.push((file, error)),
+        }
+    }
+
+    if !errors.is_empty() {
+        eprintln!("{} runs failed:", errors.len());
+
+        for (file, error) in &errors {
+            eprintln!("    {}: {}", file.display(), error);
+        }
+    }
+
+    Ok(())
+}
+
+/// Layout of delimited text runs from the config at `path`, or from `config.json` if it
+/// exists, the default layout otherwise.
+fn text_format(path: Option<&str>) -> Result<TextFormat, String> {
+    match path {
+        Some(path) => Ok(Config::load(path)?.text_format),
+        None if Path::new(CONFIG).exists() => Ok(Config::load(CONFIG)?.text_format),
+        None => Ok(TextFormat::default()),
+    }
+}
+
+fn parse_theme(name: &str) -> Result<Theme, String> {
+    match name {
+        "light" => Ok(Theme::light()),
+        "dark" => Ok(Theme::dark()),
+        other => Err(format!("Unknown theme {}.", other)),
+    }
+}
+
+fn compare(args: &[String]) -> Result<(), String> {
+    let mut config_path = None;
+    let mut layout = Layout::Overlay;
+    let mut align = None;
+    let mut normalize = None;
+    let mut window = None;
+    let mut size = (1600, 900);
+    let mut theme = Theme::light();
+    let mut output = "comparison.png";
+    let mut files = vec![];
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .map(String::as_str)
+                .ok_or(format!("{} needs a value.\n{}", arg, USAGE))
+        };
+
+        match arg.as_str() {
+            "--config" => config_path = Some(value()?),
+            "--stacked" => layout = Layout::Stacked,
+            "--align" => align = Some(value()?),
+            "--normalize" => normalize = Some(value()?),
+            "--window" => window = Some(parse_pair::<f64>(value()?, ':')?),
+            "--size" => size = parse_pair(value()?, 'x')?,
+            "--theme" => theme = parse_theme(value()?)?,
+            "--out" => output = value()?,
+            flag if flag.starts_with("--") => {
+                return Err(format!("Unknown option {}.\n{}", flag, USAGE));
+            }
+            file => files.push(file),
+        }
+    }
+
+    if files.is_empty() {
+        return Err(format!("No runs to compare.\n{}", USAGE));
+    }
+
+    let text_format = text_format(config_path)?;
+    let runs = files
+        .iter()
+        .map(|file| {
+            signal_pipeline::io::load_run(file, &text_format)
+                .map_err(|e| format!("{}: {}", file, e))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let mut comparison = Comparison::new(layout)
+        .with_size(size.0, size.1)
+        .with_theme(theme);
+    for run in &runs {
+        comparison = comparison.with_run(run)?;
+    }
+    if let Some(label) = align {
+        comparison = comparison.with_alignment(label);
+    }
+    if let Some(label) = normalize {
+        comparison = comparison.with_normalization(label);
+    }
+    if let Some((start, end)) = window {
+        comparison = comparison.with_window(start, end);
+    }
+
+    comparison
+        .save(output)
+        .map_err(|e| format!("Could not plot {}: {}", output, e))
+}
+
+/// Parses either a list of scales or a range with an optional step, e.g. `5,10,20` or `5:100:5`.
+fn parse_scales(value: &str) -> Result<Vec<f64>, String> {
+    let invalid = || format!("Invalid scales {}.", value);
+    let numbers = |separator| {
+        value
+            .split(separator)
+            .map(|n| n.trim().parse::<f64>().map_err(|_| invalid()))
+            .collect::<Result<Vec<_>, String>>()
+    };
+
+    let scales = if value.contains(':') {
+        let (start, end, step) = match numbers(':')?[..] {
+            [start, end] => (start, end, 1.),
+            [start, end, step] => (start, end, step),
+            _ => return Err(invalid()),
+        };
+
+        if step <= 0. {
+            return Err(invalid());
+        }
+
+        (0..)
+            .map(|i| start + i as f64 * step)
+            .take_while(|&scale| scale <= end)
+            .collect()
+    } else {
+        numbers(',')?
+    };
+
+    if scales.is_empty() || scales.iter().any(|&scale| scale <= 0.) {
+        return Err(invalid());
+    }
+
+    Ok(scales)
+}
+
+fn scalogram(args: &[String]) -> Result<(), String> {
+    let mut config_path = None;
+    let mut scales = parse_scales(DEFAULT_SCALES)?;
+    let mut window = None;
+    let mut size = (1600, 900);
+    let mut theme = Theme::light();
+    let mut animation = None;
+    let mut output = "scalogram.png";
+    let mut file = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .map(String::as_str)
+                .ok_or(format!("{} needs a value.\n{}", arg, USAGE))
+        };
+
+        match arg.as_str() {
+            "--config" => config_path = Some(value()?),
+            "--scales" => scales = parse_scales(value()?)?,
+            "--window" => window = Some(parse_pair::<f64>(value()?, ':')?),
+            "--size" => size = parse_pair(value()?, 'x')?,
+            "--theme" => theme = parse_theme(value()?)?,
+            "--animate" => animation = Some(value()?),
+            "--out" => output = value()?,
+            flag if flag.starts_with("--") => {
+                return Err(format!("Unknown option {}.\n{}", flag, USAGE));
+            }
+            run => file = Some(run),
+        }
+    }
+
+    let file = file.ok_or(format!("No run to analyze.\n{}", USAGE))?;
+    let run = signal_pipeline::io::load_run(file, &text_format(config_path)?)
+        .map_err(|e| format!("{}: {}", file, e))?;
+    let detector = run.detector().map_err(|e| e.to_string())?;
+
+    let mut scalogram = Scalogram::from_detector(&DDOGPeakDetector::new(scales), detector)
+        .with_title(&format!("{} ({})", run.name, run.timestamp))
+        .with_size(size.0, size.1)
+        .with_theme(theme);
+    if let Some((start, end)) = window {
+        scalogram = scalogram.with_window(start, end);
+    }
+
+    scalogram
+        .save(output)
+        .map_err(|e| format!("Could not plot {}: {}", output, e))?;
+
+    println!(
+        "{}: {} peaks from {} ridge points",
+        output,
+        scalogram.groups().len(),
+        scalogram.groups().iter().map(Vec::len).sum::<usize>()
+    );
+
+    if let Some(path) = animation {
+        scalogram
+            .animate(path, 150, 100)
+            .map_err(|e| format!("Could not animate {}: {}", path, e))?;
+    }
+
+    Ok(())
+}
+
+/// A backend that can solve the linear programs of the classification.
+fn parse_lp_backend(name: &str) -> Result<Backend, String> {
+    let backend = name.parse::<Backend>()?;
+
+    if !backend.is_lp() {
+        return Err(format!(
+            "The {} backend only fits decompositions, it cannot classify.",
+            backend
+        ));
+    }
 
-use nalgebra::DVector;
-use plotters::prelude::*;
+    Ok(backend)
+}
+
+/// Parses `<a><separator><b>`, e.g. `1600x900`.
+fn parse_pair<T: FromStr>(value: &str, separator: char) -> Result<(T, T), String> {
+    value
+        .split_once(separator)
+        .and_then(|(a, b)| Some((a.trim().parse().ok()?, b.trim().parse().ok()?)))
+        .ok_or(format!("Expected <a>{}<b>, got {}.", separator, value))
+}
 
-mod math;
-mod refrigerant;
+fn parse_baseline(value: &str) -> Result<BaselineModel, String> {
+    match value.split_once(':') {
+        None if value == "none" => Ok(BaselineModel::None),
+        None if value == "integration" => Ok(BaselineModel::Integration),
+        Some(("rolling-min", seconds)) => Ok(BaselineModel::RollingMinimum {
+            window: seconds
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid rolling minimum window {}.", seconds))?,
+        }),
+        _ => Err(format!("Unknown baseline {}.", value)),
+    }
+}
+
+/// A recipe on its own or the one a processed run was made with.
+fn load_recipe(path: &str) -> Result<Recipe, String> {
+    let json =
+        std::fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path, e))?;
 
-fn main() {
-    std::fs::read_dir("gc-data").unwrap().for_each(|f| {
-        let f = f.unwrap();
-        graph_data(read_data(f.path()), f.file_name()).unwrap();
-    });
+    serde_json::from_str::<Recipe>(&json)
+        .or_else(|_| ProcessedRun::from_json(&json).map(|processed| processed.recipe))
+        .map_err(|e| format!("{}: {}", path, e))
 }
 
-fn read_data(path: impl AsRef<Path>) -> DVector<f64> {
-    signal_pipeline::io::read_series(path).unwrap()
+fn export(args: &[String]) -> Result<(), String> {
+    let mut config_path = None;
+    let mut recipe = Recipe::default();
+    let mut smoothing = None;
+    let mut baseline = None;
+    let mut output = Path::new("export");
+    let mut files = vec![];
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .map(String::as_str)
+                .ok_or(format!("{} needs a value.\n{}", arg, USAGE))
+        };
+
+        match arg.as_str() {
+            "--config" => config_path = Some(value()?),
+            "--recipe" => recipe = load_recipe(value()?)?,
+            "--smooth" => {
+                smoothing = Some(Smoothing::MovingAverage {
+                    window: value()?
+                        .parse()
+                        .map_err(|e| format!("Invalid smoothing window: {}", e))?,
+                })
+            }
+            "--baseline" => baseline = Some(parse_baseline(value()?)?),
+            "--out" => output = Path::new(value()?),
+            flag if flag.starts_with("--") => {
+                return Err(format!("Unknown option {}.\n{}", flag, USAGE));
+            }
+            file => files.push(file),
+        }
+    }
+
+    if files.is_empty() {
+        return Err(format!("No runs to export.\n{}", USAGE));
+    }
+
+    // flags override the steps of a loaded recipe
+    recipe.smoothing = smoothing.unwrap_or(recipe.smoothing);
+    recipe.baseline = baseline.unwrap_or(recipe.baseline);
+
+    std::fs::create_dir_all(output).map_err(|e| e.to_string())?;
+    let text_format = text_format(config_path)?;
+
+    for file in files {
+        let run = signal_pipeline::io::load_run(file, &text_format)
+            .map_err(|e| format!("{}: {}", file, e))?;
+        let detector = run.detector().map_err(|e| e.to_string())?;
+        let processed = ProcessedRun::new(&run, detector, recipe)?;
+        let stem = Path::new(file)
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+
+        for (extension, contents) in [
+            ("trace.csv", trace_csv(&processed.trace())),
+            ("peaks.csv", peaks_csv(detector)),
+            ("peaks.json", peaks_json(detector)?),
+            ("processed.json", processed.to_json()?),
+        ] {
+            let path = output.join(format!("{}.{}", stem, extension));
+            std::fs::write(&path, contents)
+                .map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+        }
+
+        println!("{}: {}", file, output.join(&stem).display());
+    }
+
+    Ok(())
+}
+
+fn report(args: &[String]) -> Result<(), String> {
+    let mut config_path = CONFIG;
+    let mut backend = Backend::default();
+    let mut measurements = HashMap::new();
+    let mut output = None;
+    let mut file = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .map(String::as_str)
+                .ok_or(format!("{} needs a value.\n{}", arg, USAGE))
+        };
+
+        match arg.as_str() {
+            "--config" => config_path = value()?,
+            "--backend" => backend = parse_lp_backend(value()?)?,
+            "--measure" => {
+                let measurement = value()?;
+                let (name, value) = measurement
+                    .split_once('=')
+                    .and_then(|(name, v)| Some((name.trim(), v.trim().parse::<f64>().ok()?)))
+                    .ok_or(format!("Expected NAME=VALUE, got {}.", measurement))?;
+
+                measurements.insert(name.to_string(), value);
+            }
+            "--out" => output = Some(PathBuf::from(value()?)),
+            flag if flag.starts_with("--") => {
+                return Err(format!("Unknown option {}.\n{}", flag, USAGE));
+            }
+            run => file = Some(Path::new(run)),
+        }
+    }
+
+    let file = file.ok_or(format!("No run to report on.\n{}", USAGE))?;
+    let output = output.unwrap_or_else(|| file.with_extension("html"));
+
+    let config = Config::load(config_path)?;
+    let registry = config.registry()?;
+    let run = signal_pipeline::io::load_run(file, &config.text_format)
+        .map_err(|e| format!("{}: {}", file.display(), e))?;
+    let analysis = run::analyze(
+        run.detector().map_err(|e| e.to_string())?,
+        &config,
+        &registry,
+        backend,
+    )?;
+
+    let html = HtmlReport::new(&run, &config, &registry, &analysis)?
+        .with_source(&file.file_name().unwrap_or_default().to_string_lossy())
+        .with_measurements(measurements)
+        .render()?;
+
+    std::fs::write(&output, html)
+        .map_err(|e| format!("Could not write {}: {}", output.display(), e))?;
+
+    println!("{}", output.display());
+
+    Ok(())
 }
 
-fn graph_data(data: DVector<f64>, name: OsString) -> Result<(), Box<dyn Error>> {
+fn batch(args: &[String]) -> Result<(), String> {
+    let mut config_path = CONFIG;
+    let mut output = "results";
+    let mut backend = Backend::default();
+    let mut threads = None;
+    let mut data_dir = DATA_DIR;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .map(String::as_str)
+                .ok_or(format!("{} needs a value.\n{}", arg, USAGE))
+        };
+
+        match arg.as_str() {
+            "--config" => config_path = value()?,
+            "--out" => output = value()?,
+            "--backend" => backend = parse_lp_backend(value()?)?,
+            "--threads" => {
+                threads = Some(
+                    value()?
+                        .parse::<usize>()
+                        .map_err(|e| format!("Invalid thread count: {}", e))?,
+                )
+            }
+            flag if flag.starts_with("--") => {
+                return Err(format!("Unknown option {}.\n{}", flag, USAGE));
+            }
+            dir => data_dir = dir,
+        }
+    }
+
+    let config = Config::load(config_path)?;
+    // delimited text runs have no peak table to analyze
+    let files = run_files(data_dir, &PEAK_TABLE_EXTENSIONS)?;
+
+    if config.response_factors.is_empty() {
+        eprintln!("No response factors configured, area percent is used as mole fraction.");
+    }
+
+    let mut batch = Batch::new(&config, output)?.with_backend(backend);
+    if let Some(threads) = threads {
+        batch = batch.with_threads(threads);
+    }
+
     let time = Instant::now();
-    let name = format!(
-        "gc-data-img/{}.png",
-        name.to_string_lossy()
-            .to_string()
-            .trim_end_matches(".fusion-data")
+    let outcome = batch.run(&files)?;
+
+    println!(
+        "Processed {} of {} runs in {:?}, results in {}.",
+        outcome.runs.len(),
+        files.len(),
+        Instant::now() - time,
+        output
     );
 
-    let root = BitMapBackend::new(&name, (480, 320)).into_drawing_area();
-    root.fill(&WHITE)?;
-    let mut chart = ChartBuilder::on(&root)
-        .caption("diggity", ("sans-serif", 50).into_font())
-        .margin(5)
-        .x_label_area_size(30)
-        .y_label_area_size(30)
-        .build_cartesian_2d(0..data.len(), -50.0..data.max())?;
+    if !outcome.errors.is_empty() {
+        eprintln!("{} runs failed:", outcome.errors.len());
+
+        for error in &outcome.errors {
+            eprintln!("    {}: {}", error.file.display(), error.error);
+        }
+    }
+
+    Ok(())
+}
 
-    chart.configure_mesh().draw()?;
+fn catalog(args: &[String]) -> Result<(), String> {
+    let mut config_path = None;
+    let mut output = CATALOG;
+    let mut data_dir = DATA_DIR;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .map(String::as_str)
+                .ok_or(format!("{} needs a value.\n{}", arg, USAGE))
+        };
+
+        match arg.as_str() {
+            "--config" => config_path = Some(value()?),
+            "--out" => output = value()?,
+            flag if flag.starts_with("--") => {
+                return Err(format!("Unknown option {}.\n{}", flag, USAGE));
+            }
+            dir => data_dir = dir,
+        }
+    }
+
+    // an existing catalog of the same directory is refreshed, anything else starts over
+    let mut catalog = match Catalog::load(output) {
+        Ok(catalog) if catalog.directory == Path::new(data_dir) => catalog,
+        Ok(_) => Catalog::new(data_dir),
+        Err(e) => {
+            if Path::new(output).exists() {
+                eprintln!("Rebuilding {}: {}", output, e);
+            }
+            Catalog::new(data_dir)
+        }
+    };
 
-    chart
-        .draw_series(LineSeries::new(data.iter().copied().enumerate(), &RED))?
-        .label("y = DATYAAAA")
-        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &RED));
+    let time = Instant::now();
+    let outcome = catalog.refresh(&text_format(config_path)?)?;
+    catalog.save(output)?;
+
+    println!(
+        "{} runs in {}: {} read, {} unchanged, {} removed in {:?}.",
+        catalog.entries.len(),
+        output,
+        outcome.read,
+        outcome.kept,
+        outcome.removed,
+        Instant::now() - time
+    );
 
-    root.present()?;
+    if !outcome.errors.is_empty() {
+        eprintln!("{} files could not be read:", outcome.errors.len());
 
-    println!("{}: {:?}", name, Instant::now() - time);
+        for error in &outcome.errors {
+            eprintln!("    {}: {}", error.file.display(), error.error);
+        }
+    }
 
     Ok(())
 }
 
-// fn main() {
-
-//     let mut config: Config = serde_json::de::from_reader(
-//         File::open("config.json").expect("Could not find config file."),
-//     )
-//     .expect("Could not read config file.");
-
-//     config.init_pure_mixtures();
-
-//     config.sanity_check().expect("Config sanity check failed.");
-
-//     println!("Please enter reading: ");
-
-//     let mut input = String::new();
-
-//     io::stdin()
-//         .read_line(&mut input)
-//         .expect("Unable to read user input");
-
-//     let reading: GCReading = input.try_into().expect("Unable to parse user input");
-
-//     let mut results: Vec<ClassificationResult> = config
-//         .mixtures
-//         .iter()
-//         .filter_map(|mix| mix.classify(&reading))
-//         .collect();
-
-//     let mut results2: Vec<ClassificationResult> = config
-//         .mixtures
-//         .iter()
-//         .filter_map(|mix| mix.classify_optimize(&reading))
-//         .collect();
-
-//     results.sort_by(|r1, r2| r1.purity.partial_cmp(&r2.purity).unwrap().reverse());
-//     results2.sort_by(|r1, r2| r1.purity.partial_cmp(&r2.purity).unwrap().reverse());
-
-//     println!("\nClassifications:");
-
-//     results.iter().for_each(|res| println!("{}", res));
-
-//     println!("\nOptimization Classifications:");
-
-//     results2.iter().for_each(|res| println!("{}", res));
-
-//     let (mut results3, total_usage) = MixtureOptimization::new(
-//         &reading,
-//         config
-//             .mixtures
-//             .iter()
-//             .filter(|m| math::valid_comparison(&reading, m))
-//             .map(|m| (m, 0.))
-//             .collect(),
-//     )
-//     .optimize_usage()
-//     .expect("idk");
-
-//     println!("\nTotal Optimization ({:.3}% usage):", total_usage * 100.0);
-
-//     results3.sort_by(|r1, r2| r1.0.partial_cmp(&r2.0).unwrap().reverse());
-
-//     results3.iter().for_each(|(percent, mix)| {
-//         println!(
-//             "Name: {}, Percent: {:.3}%",
-//             mix.identifier(),
-//             percent * 100.0
-//         )
-//     });
-// }
-
-// #[derive(Deserialize, Debug)]
-// struct Config<'a> {
-//     pure_refrigerants: HashSet<RefrigerantName>,
-//     mixtures: Vec<RefrigerantMixture<'a>>,
-// }
-
-// impl<'a> Config<'a> {
-//     fn init_pure_mixtures(&mut self) {
-//         for r in self.pure_refrigerants.iter() {
-//             if !self.mixtures.iter().any(|m| m.identifier() == r) {
-//                 self.mixtures.push(RefrigerantMixture::new(
-//                     r.clone(),
-//                     HashMap::from([(r.clone(), 1.0)]),
-//                     ClassificationList::default(),
-//                 ));
-//             }
-//         }
-//     }
-
-//     fn sanity_check(&self) -> Result<(), String> {
-//         match self.mixtures.iter().find(|mix| {
-//             mix.components()
-//                 .map(|r| *r.1)
-//                 .sum::<f64>()
-//                 .sub(1.)
-//                 .abs()
-//                 .gt(&f64::EPSILON)
-//         }) {
-//             Some(mix) => {
-//                 return Err(format!(
-//                     "Mixture [{}]'s components do not add up to 1.0. (Make sure you are listing them with proportions instead of percentages!)",
-//                     mix.identifier()
-//                 ));
-//             }
-//             None => (),
-//         }
-
-//         Ok(())
-//     }
-// }
+fn query(args: &[String]) -> Result<(), String> {
+    let mut catalog_path = CATALOG;
+    let mut query = Query::default();
+    let mut paths = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .map(String::as_str)
+                .ok_or(format!("{} needs a value.\n{}", arg, USAGE))
+        };
+
+        match arg.as_str() {
+            "--catalog" => catalog_path = value()?,
+            "--sample" => query = query.with_sample(value()?),
+            "--serial" => query = query.with_serial_number(value()?),
+            "--method" => query = query.with_method(value()?),
+            "--tag" => query = query.with_tag(value()?),
+            "--date" => {
+                let date = value()?;
+                query = query.with_period(Some(date), Some(date));
+            }
+            "--from" => query.from = Some(value()?.into()),
+            "--to" => query.to = Some(value()?.into()),
+            "--paths" => paths = true,
+            other => return Err(format!("Unknown option {}.\n{}", other, USAGE)),
+        }
+    }
+
+    let catalog = Catalog::load(catalog_path)
+        .map_err(|e| format!("{}\nBuild the catalog with gc-analyzer catalog.", e))?;
+
+    for entry in catalog.query(&query) {
+        if paths {
+            println!("{}", catalog.path(entry).display());
+        } else {
+            println!(
+                "{}\t{}\t{}\t{}\t{}",
+                entry.timestamp,
+                entry.serial_number,
+                entry.sample,
+                entry.method,
+                catalog.path(entry).display()
+            );
+        }
+    }
+
+    Ok(())
+}
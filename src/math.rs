@@ -1,115 +1,420 @@
-use std::collections::BTreeSet;
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::Display,
+    sync::Arc,
+};
+
+use nalgebra::{DMatrix, DVector};
 
-extern crate good_lp;
+use refrigerants::{Detection, GCReading, RefrigerantMixture, RefrigerantName};
 
-use good_lp::{
-    Constraint, Expression, IntoAffineExpression, ProblemVariables, Solution, SolverModel,
-    Variable, variable, variables,
+use crate::{
+    concentration,
+    solver::{self, Backend},
 };
 
-use refrigerants::{GCReading, RefrigerantMixture, RefrigerantName};
+pub type OptimizationResult = Result<(Vec<(f64, Arc<RefrigerantMixture>)>, f64), String>;
 
-pub type OptimizationResult<'a> = Result<(Vec<(f64, &'a RefrigerantMixture<'a>)>, f64), String>;
+/// Residual penalty used by [`MixtureOptimization::decompose`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Loss {
+    /// Weighted sum of squared residuals, solved as non-negative least squares.
+    Squared,
+    /// Weighted sum of absolute residuals, less sensitive to a single badly quantified peak.
+    /// Needs an LP backend.
+    Absolute,
+}
 
-pub struct MixtureOptimization<'a> {
-    problem_variables: ProblemVariables,
-    ref_vars: Vec<(Variable, &'a RefrigerantMixture<'a>)>,
-    component_expressions: Vec<Expression>,
-    constraints: Vec<Constraint>,
+/// Result of fitting a reading as a non-negative combination of mixtures.
+#[derive(Debug, Clone)]
+pub struct Decomposition {
+    pub concentrations: Vec<(f64, Arc<RefrigerantMixture>)>,
+    /// Observed minus fitted fraction for every component of the reading or the mixtures.
+    pub residuals: Vec<(RefrigerantName, f64)>,
+    /// Weighted sum of squared residuals, a chi-square when the weights are `1 / sigma^2`.
+    pub weighted_sse: f64,
+    /// Uncentered coefficient of determination, 1 for a perfect fit.
+    pub r_squared: f64,
 }
 
-impl<'a> MixtureOptimization<'a> {
-    pub fn new(reading: &GCReading, mixtures: Vec<(&'a RefrigerantMixture, f64)>) -> Self {
-        let component_set = generate_component_set(&mixtures);
-        let mut problem_variables = variables! {};
+pub struct MixtureOptimization {
+    mixtures: Vec<Arc<RefrigerantMixture>>,
+    /// Components of the reading and the mixtures, rows of `compositions`.
+    components: Vec<RefrigerantName>,
+    /// Mixture fractions in the reading's basis, one column per mixture.
+    compositions: DMatrix<f64>,
+    observed: DVector<f64>,
+    /// Largest amount of each component the reading allows, see [`GCReading::upper_bound`].
+    upper_bounds: DVector<f64>,
+    lower_bounds: DVector<f64>,
+    backend: Backend,
+}
 
-        let vars: Vec<(Variable, Vec<f64>)> = mixtures
-            .iter()
-            .map(|(mix, min)| {
-                (
-                    problem_variables.add(variable().min(*min).max(1.)),
-                    vectorize(mix, component_set.as_slice()),
-                )
-            })
-            .collect();
+/// Concentrations of the same problem solved by two backends.
+#[derive(Debug, Clone)]
+pub struct CrossCheck {
+    pub primary: (Backend, Vec<(f64, Arc<RefrigerantMixture>)>),
+    pub secondary: (Backend, Vec<(f64, Arc<RefrigerantMixture>)>),
+    pub tolerance: f64,
+}
 
-        let ref_vars = vars
+impl MixtureOptimization {
+    /// Sets up the problem in the basis of `reading`, converting the mixture compositions so
+    /// that both sides of every constraint are in the same units.
+    pub fn new(
+        reading: &GCReading,
+        mixtures: Vec<(Arc<RefrigerantMixture>, f64)>,
+    ) -> Result<Self, String> {
+        let fractions = mixtures
             .iter()
-            .enumerate()
-            .map(|(i, (var, _))| (var.clone(), mixtures[i].0))
-            .collect::<Vec<_>>();
+            .map(|(mix, _)| mix.fractions(reading.basis()))
+            .collect::<Result<Vec<_>, String>>()?;
 
-        let component_expressions = make_constraint_expressions(vars);
+        let components = generate_component_set(&mixtures)
+            .into_iter()
+            .chain(reading.components().map(|(name, _)| name))
+            .collect::<BTreeSet<&RefrigerantName>>()
+            .into_iter()
+            .cloned()
+            .collect::<Vec<_>>();
 
-        let constraints = component_expressions
+        let columns = fractions
             .iter()
-            .cloned()
-            .enumerate()
-            .map(|(i, ex)| ex.leq(reading.get_component(component_set[i]).map_or(0., |&c| c)))
+            .map(|fractions| {
+                DVector::from_iterator(
+                    components.len(),
+                    components
+                        .iter()
+                        .map(|c| fractions.get(c).copied().unwrap_or(0.)),
+                )
+            })
             .collect::<Vec<_>>();
 
-        Self {
-            problem_variables,
-            ref_vars,
-            component_expressions,
-            constraints,
-        }
-    }
+        let compositions = DMatrix::from_columns(&columns);
+        let observed = DVector::from_iterator(
+            components.len(),
+            components
+                .iter()
+                .map(|c| reading.get_component(c).copied().unwrap_or(0.)),
+        );
+        let upper_bounds = DVector::from_iterator(
+            components.len(),
+            components.iter().map(|c| reading.upper_bound(c)),
+        );
+        let lower_bounds = DVector::from_iterator(mixtures.len(), mixtures.iter().map(|m| m.1));
 
-    pub fn optimize_usage(self) -> OptimizationResult<'a> {
-        let obj = self.component_expressions.iter().sum::<Expression>();
+        Ok(Self {
+            mixtures: mixtures.iter().map(|(mix, _)| mix.clone()).collect(),
+            components,
+            compositions,
+            observed,
+            upper_bounds,
+            lower_bounds,
+            backend: Backend::default(),
+        })
+    }
 
-        self.optimize(&obj)
+    /// Solves the problem with `backend` instead of [`Backend::default`].
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
     }
 
-    fn optimize(self, objective_function: &Expression) -> OptimizationResult<'a> {
-        let sol = self
-            .problem_variables
-            .maximise(objective_function)
-            .using(good_lp::solvers::clarabel::clarabel)
-            .with_all(self.constraints)
-            .solve()
-            .map_err(|e| e.to_string())?;
+    /// Fits the reading as a non-negative combination of the mixtures by minimizing the
+    /// weighted residual, instead of maximizing explained composition under `component <=
+    /// reading` constraints. Unlike [`Self::optimize_usage`] a mixture can over-explain a
+    /// component that measurement noise put slightly low.
+    ///
+    /// `weights` needs an entry for every component of the reading and the mixtures, see
+    /// [`MeasurementUncertainty::weights`]. The mixture minimums given to [`Self::new`] are kept
+    /// as lower bounds.
+    ///
+    /// [`MeasurementUncertainty::weights`]: crate::uncertainty::MeasurementUncertainty::weights
+    pub fn decompose(
+        self,
+        loss: Loss,
+        weights: &HashMap<RefrigerantName, f64>,
+    ) -> Result<Decomposition, String> {
+        if self.compositions.ncols() == 0 {
+            return Err("No mixtures to decompose the reading into.".into());
+        }
 
-        let concentrations = self
-            .ref_vars
-            .iter()
-            .map(|(var, mix)| (sol.value(*var), *mix))
-            .collect::<Vec<_>>();
+        let weights = DVector::from_vec(
+            self.components
+                .iter()
+                .map(|c| {
+                    weights
+                        .get(c)
+                        .copied()
+                        .ok_or(format!("No weight for component {}.", c))
+                })
+                .collect::<Result<Vec<_>, String>>()?,
+        );
+
+        if weights.iter().any(|w| !w.is_finite() || *w <= 0.) {
+            return Err("Component weights must be positive and finite.".into());
+        }
+
+        // substitute x = lower + y so that the solvers only need y >= 0
+        let shifted = &self.observed - &self.compositions * &self.lower_bounds;
+
+        let y = match loss {
+            Loss::Squared => {
+                let sqrt_weights = weights.map(f64::sqrt);
+                let mut a = self.compositions.clone();
+
+                a.row_iter_mut()
+                    .zip(sqrt_weights.iter())
+                    .for_each(|(mut row, w)| row *= *w);
+
+                nnls(&a, &shifted.component_mul(&sqrt_weights))
+            }
+            Loss::Absolute => least_absolute(self.backend, &self.compositions, &shifted, &weights)?,
+        };
+
+        let x = y + &self.lower_bounds;
+        let residuals = &self.observed - &self.compositions * &x;
+
+        let weighted_sse = residuals.component_mul(&residuals).dot(&weights);
+        let weighted_sst = self.observed.component_mul(&self.observed).dot(&weights);
+
+        Ok(Decomposition {
+            concentrations: x
+                .iter()
+                .copied()
+                .zip(self.mixtures.iter().cloned())
+                .collect(),
+            residuals: self
+                .components
+                .into_iter()
+                .zip(residuals.iter().copied())
+                .collect(),
+            weighted_sse,
+            r_squared: if weighted_sst > 0. {
+                1. - weighted_sse / weighted_sst
+            } else {
+                0.
+            },
+        })
+    }
 
-        Ok((concentrations, sol.eval(objective_function)))
+    /// Maximizes the explained part of the reading without any component exceeding what was
+    /// measured.
+    pub fn optimize_usage(self) -> OptimizationResult {
+        self.optimize(self.backend, &self.usage())
     }
 
-    pub fn optimize_max_refrigerant(self, name: &RefrigerantName) -> OptimizationResult<'a> {
+    /// Like [`Self::optimize_usage`], but prefers the mixture called `name` among equally good
+    /// solutions.
+    pub fn optimize_max_refrigerant(self, name: &RefrigerantName) -> OptimizationResult {
         match self
-            .ref_vars
+            .mixtures
             .iter()
-            .find(|&(_, mix)| mix.identifier() == name)
+            .position(|mix| mix.identifier() == name)
         {
-            Some((var, _)) => {
-                let obj = self.component_expressions.iter().sum::<Expression>() + var;
+            Some(i) => {
+                let mut objective = self.usage();
+                objective[i] += 1.;
 
-                self.optimize(&obj)
+                self.optimize(self.backend, &objective)
             }
             None => {
                 Err("Requested max refrigerant is not a part of the optimization problem.".into())
             }
         }
     }
-}
 
-/// finds the farthest common component of the two mixtures
-///
-/// assumes mixtures have the same components
-fn find_weakest_component(observed: &GCReading, target: &RefrigerantMixture) -> f64 {
-    target
-        .components()
-        .map(|(name, concentration)| {
-            let observed_concentration = observed.get_component(name).unwrap();
-            (observed_concentration / concentration).min(1.0)
+    /// Runs [`Self::optimize_usage`] with both the configured backend and `secondary`, so
+    /// solver trouble shows up as a disagreement instead of a wrong purity.
+    pub fn cross_check_usage(
+        &self,
+        secondary: Backend,
+        tolerance: f64,
+    ) -> Result<CrossCheck, String> {
+        let objective = self.usage();
+
+        Ok(CrossCheck {
+            primary: (self.backend, self.optimize(self.backend, &objective)?.0),
+            secondary: (secondary, self.optimize(secondary, &objective)?.0),
+            tolerance,
         })
-        .reduce(|a, b| a.min(b))
-        .unwrap()
+    }
+
+    /// Explained fraction of the reading per unit of each mixture.
+    fn usage(&self) -> DVector<f64> {
+        self.compositions.row_sum().transpose()
+    }
+
+    /// Solves the linear program, an error for [`Backend::Nnls`] which only fits.
+    fn optimize(&self, backend: Backend, objective: &DVector<f64>) -> OptimizationResult {
+        let upper = DVector::from_element(self.mixtures.len(), 1.);
+
+        let x = solver::solve_lp(
+            backend,
+            objective,
+            &self.compositions,
+            &self.upper_bounds,
+            &self.lower_bounds,
+            &upper,
+        )?;
+
+        Ok((
+            x.iter()
+                .copied()
+                .zip(self.mixtures.iter().cloned())
+                .collect(),
+            objective.dot(&x),
+        ))
+    }
+}
+
+impl CrossCheck {
+    /// Mixtures whose concentrations differ by more than the tolerance, with the primary and
+    /// secondary value.
+    pub fn disagreements(&self) -> Vec<(Arc<RefrigerantMixture>, f64, f64)> {
+        self.primary
+            .1
+            .iter()
+            .zip(self.secondary.1.iter())
+            .filter(|((a, _), (b, _))| (a - b).abs() > self.tolerance)
+            .map(|((a, mix), (b, _))| (mix.clone(), *a, *b))
+            .collect()
+    }
+
+    pub fn agrees(&self) -> bool {
+        self.disagreements().is_empty()
+    }
+}
+
+impl Display for CrossCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let disagreements = self.disagreements();
+
+        if disagreements.is_empty() {
+            return write!(f, "{} and {} agree", self.primary.0, self.secondary.0);
+        }
+
+        write!(f, "{} and {} disagree:", self.primary.0, self.secondary.0)?;
+
+        for (mix, a, b) in disagreements {
+            write!(
+                f,
+                "\n  {}: {:.3}% vs {:.3}%",
+                mix.identifier(),
+                a * 100.,
+                b * 100.
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Lawson-Hanson active set solver for `min |ax - b|^2` subject to `x >= 0`.
+fn nnls(a: &DMatrix<f64>, b: &DVector<f64>) -> DVector<f64> {
+    const TOLERANCE: f64 = 1e-12;
+
+    let n = a.ncols();
+    let mut x = DVector::zeros(n);
+    let mut passive = vec![false; n];
+
+    for _ in 0..3 * n {
+        let gradient = a.tr_mul(&(b - a * &x));
+
+        let Some((next, _)) = (0..n)
+            .filter(|&j| !passive[j] && gradient[j] > TOLERANCE)
+            .map(|j| (j, gradient[j]))
+            .max_by(|l, r| l.1.total_cmp(&r.1))
+        else {
+            break;
+        };
+
+        passive[next] = true;
+
+        loop {
+            let columns: Vec<usize> = (0..n).filter(|&j| passive[j]).collect();
+
+            if columns.is_empty() {
+                break;
+            }
+            let sub = a.select_columns(&columns);
+            let solution = sub
+                .svd(true, true)
+                .solve(b, TOLERANCE)
+                .expect("SVD was computed with both U and V.");
+
+            let mut s = DVector::zeros(n);
+            columns
+                .iter()
+                .zip(solution.iter())
+                .for_each(|(&j, &v)| s[j] = v);
+
+            if columns.iter().all(|&j| s[j] > TOLERANCE) {
+                x = s;
+                break;
+            }
+
+            // step towards the unconstrained solution until the first variable hits zero
+            let alpha = columns
+                .iter()
+                .filter(|&&j| s[j] <= TOLERANCE && x[j] - s[j] > 0.)
+                .map(|&j| x[j] / (x[j] - s[j]))
+                .fold(f64::INFINITY, f64::min);
+
+            // no variable can move, e.g. a column the solve dropped as degenerate while it was at
+            // zero. Such columns are at zero already and leave the passive set below.
+            if alpha.is_finite() {
+                x += (s - &x) * alpha;
+            }
+
+            for &j in &columns {
+                if x[j] <= TOLERANCE {
+                    passive[j] = false;
+                    x[j] = 0.;
+                }
+            }
+        }
+    }
+
+    x
+}
+
+/// `min sum(w * |ax - b|)` subject to `x >= 0`, as a linear program over `x` and the absolute
+/// residuals `t`: `max -w t` subject to `ax - t <= b` and `-ax - t <= -b`.
+fn least_absolute(
+    backend: Backend,
+    a: &DMatrix<f64>,
+    b: &DVector<f64>,
+    weights: &DVector<f64>,
+) -> Result<DVector<f64>, String> {
+    let (rows, cols) = a.shape();
+    let identity = DMatrix::<f64>::identity(rows, rows);
+
+    let mut constraints = DMatrix::zeros(2 * rows, cols + rows);
+    constraints.view_mut((0, 0), (rows, cols)).copy_from(a);
+    constraints
+        .view_mut((0, cols), (rows, rows))
+        .copy_from(&-&identity);
+    constraints.view_mut((rows, 0), (rows, cols)).copy_from(&-a);
+    constraints
+        .view_mut((rows, cols), (rows, rows))
+        .copy_from(&-&identity);
+
+    let mut objective = DVector::zeros(cols + rows);
+    objective.rows_mut(cols, rows).copy_from(&-weights);
+
+    let bounds = DVector::from_iterator(2 * rows, b.iter().copied().chain(b.iter().map(|v| -v)));
+
+    let solution = solver::solve_lp(
+        backend,
+        &objective,
+        &constraints,
+        &bounds,
+        &DVector::zeros(cols + rows),
+        &DVector::from_element(cols + rows, f64::INFINITY),
+    )?;
+
+    Ok(solution.rows(0, cols).into_owned())
 }
 
 pub fn valid_comparison(observed: &GCReading, target: &RefrigerantMixture) -> bool {
@@ -118,52 +423,44 @@ pub fn valid_comparison(observed: &GCReading, target: &RefrigerantMixture) -> bo
         .is_superset(&target.component_set())
 }
 
+/// Concentration of `target` limited by its scarcest component, `None` if the reading lacks
+/// one of its components. See [`concentration::estimate`] for partial readings.
 pub fn find_concentration(observed: &GCReading, target: &RefrigerantMixture) -> Option<f64> {
     if !valid_comparison(observed, target) {
         return None;
     }
 
-    Some(find_weakest_component(observed, target))
+    concentration::estimate(observed, target, 0.)
+        .ok()
+        .map(|estimate| estimate.concentration)
 }
 
-fn make_constraint_expressions(vars: Vec<(Variable, Vec<f64>)>) -> Vec<Expression> {
-    vars.into_iter()
-        .map(|(var, vec)| {
-            vec.into_iter()
-                .map(move |val| val * var.into_expression())
-                .collect::<Vec<_>>()
-        })
-        .reduce(|e1, e2| {
-            e1.into_iter()
-                .zip(e2.into_iter())
-                .map(|(e1, e2)| e1 + e2)
-                .collect()
-        })
-        .expect("Failed to collect constraints.")
-}
+/// Fraction below which a detected component that is not part of the target counts as a low
+/// level contaminant rather than another refrigerant mixed in.
+pub const DEFAULT_LOW_THRESHOLD: f64 = 0.05;
 
-fn is_low(&(name, &concentration): &(&RefrigerantName, &f64), target: &RefrigerantMixture) -> bool {
-    concentration <= 0.05 && !target.component_set().contains(name)
+fn is_low(
+    observed: &GCReading,
+    &(name, &concentration): &(&RefrigerantName, &f64),
+    target: &RefrigerantMixture,
+    threshold: f64,
+) -> bool {
+    concentration <= threshold
+        && !target.component_set().contains(name)
+        && observed.detection(name) != Detection::NotDetected
 }
 
-pub fn find_max_low(observed: &GCReading, target: &RefrigerantMixture) -> f64 {
+/// Largest low level component that is not part of `target`, 0 if there is none. Components
+/// that were not detected don't count, ones below the quantitation limit count at their value.
+pub fn find_max_low(observed: &GCReading, target: &RefrigerantMixture, threshold: f64) -> f64 {
     observed
         .components()
-        .filter(|v| is_low(v, target))
+        .filter(|v| is_low(observed, v, target, threshold))
         .map(|(_, &v)| v)
-        .reduce(|v1, v2| if v1 < v2 { v1 } else { v2 })
-        .unwrap_or(0.0)
-}
-
-fn vectorize(mix: &RefrigerantMixture, keys: &[&RefrigerantName]) -> Vec<f64> {
-    keys.iter()
-        .map(|&key| mix.get_component(key).map_or(0., |v| *v))
-        .collect()
+        .fold(0., f64::max)
 }
 
-fn generate_component_set<'a>(
-    targets: &[(&'a RefrigerantMixture, f64)],
-) -> Vec<&'a RefrigerantName> {
+fn generate_component_set(targets: &[(Arc<RefrigerantMixture>, f64)]) -> Vec<&RefrigerantName> {
     targets
         .iter()
         .flat_map(|(mix, _)| mix.component_set())
@@ -171,3 +468,169 @@ fn generate_component_set<'a>(
         .into_iter()
         .collect::<Vec<&RefrigerantName>>()
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use nalgebra::{DMatrix, DVector};
+    use refrigerants::DetectionLimits;
+
+    use crate::test_util::{mixture, name, reading};
+
+    use super::*;
+
+    #[test]
+    fn nnls_clamps_negative_coefficients() {
+        let a = DMatrix::from_row_slice(3, 2, &[1., 0., 0., 1., 1., 1.]);
+        let b = DVector::from_column_slice(&[1., -1., 0.5]);
+
+        let x = nnls(&a, &b);
+
+        assert!(x[1] == 0.);
+        assert!((x[0] - 0.75).abs() < 1e-9);
+    }
+
+    #[test]
+    fn nnls_degenerate_columns() {
+        // two identical mixtures, and a column that explains nothing
+        let a = DMatrix::from_row_slice(3, 3, &[0.5, 0.5, 0., 0.5, 0.5, 0., 0., 0., 0.]);
+        let b = DVector::from_column_slice(&[0.5, 0.5, 0.]);
+
+        let x = nnls(&a, &b);
+
+        assert!(x.iter().all(|v| v.is_finite() && *v >= 0.), "{x}");
+        assert!((&a * &x - &b).norm() < 1e-9, "{x}");
+
+        // a column below the SVD cutoff solves to exactly zero from zero
+        let a = DMatrix::from_row_slice(1, 1, &[1e-13]);
+        let b = DVector::from_column_slice(&[100.]);
+
+        let x = nnls(&a, &b);
+
+        assert_eq!(x[0], 0.);
+    }
+
+    #[test]
+    fn decompose_over_explained_component() {
+        let r410a = mixture("r-410a", &[("r-32", 0.5), ("r-125", 0.5)]);
+        let r22 = mixture("r-22", &[("r-22", 1.)]);
+
+        // noise put R-32 above what 97% R-410A would explain
+        let reading = reading(&[("r-32", 0.49), ("r-125", 0.48), ("r-22", 0.03)]);
+
+        for loss in [Loss::Squared, Loss::Absolute] {
+            let weights = reading
+                .components()
+                .map(|(name, _)| (name.clone(), 1.))
+                .collect();
+            let result =
+                MixtureOptimization::new(&reading, vec![(r410a.clone(), 0.), (r22.clone(), 0.)])
+                    .unwrap()
+                    .decompose(loss, &weights)
+                    .unwrap();
+
+            let concentration = |id: &str| {
+                result
+                    .concentrations
+                    .iter()
+                    .find(|(_, m)| m.identifier() == &name(id))
+                    .unwrap()
+                    .0
+            };
+
+            assert!((concentration("r-410a") - 0.97).abs() < 0.011, "{loss:?}");
+            assert!((concentration("r-22") - 0.03).abs() < 1e-6, "{loss:?}");
+            assert!(result.r_squared > 0.99);
+            assert_eq!(result.residuals.len(), 3);
+        }
+    }
+
+    #[test]
+    fn max_low_is_largest_contaminant() {
+        let r410a = mixture("r-410a", &[("r-32", 0.5), ("r-125", 0.5)]);
+        let reading = reading(&[
+            ("r-32", 0.45),
+            ("r-125", 0.45),
+            ("r-22", 0.03),
+            ("r-134a", 0.01),
+            ("r-12", 0.06),
+        ]);
+
+        assert_eq!(find_max_low(&reading, &r410a, DEFAULT_LOW_THRESHOLD), 0.03);
+        assert_eq!(find_max_low(&reading, &r410a, 0.1), 0.06);
+        assert_eq!(find_concentration(&reading, &r410a), Some(0.9));
+
+        // a peak below the detection limit is not a contaminant
+        let reading = reading.with_limits(HashMap::from([(
+            name("r-22"),
+            DetectionLimits::new(0.05, 0.1).unwrap(),
+        )]));
+        assert_eq!(find_max_low(&reading, &r410a, DEFAULT_LOW_THRESHOLD), 0.01);
+    }
+
+    #[test]
+    fn backends_agree_on_usage() {
+        let r410a = mixture("r-410a", &[("r-32", 0.5), ("r-125", 0.5)]);
+        let r22 = mixture("r-22", &[("r-22", 1.)]);
+        let reading = reading(&[("r-32", 0.48), ("r-125", 0.49), ("r-22", 0.03)]);
+
+        for backend in Backend::ALL.iter().filter(|b| b.is_lp()) {
+            let (concentrations, usage) =
+                MixtureOptimization::new(&reading, vec![(r410a.clone(), 0.), (r22.clone(), 0.)])
+                    .unwrap()
+                    .with_backend(*backend)
+                    .optimize_usage()
+                    .unwrap();
+
+            assert!((concentrations[0].0 - 0.96).abs() < 1e-6, "{backend}");
+            assert!((concentrations[1].0 - 0.03).abs() < 1e-6, "{backend}");
+            assert!((usage - 0.99).abs() < 1e-6, "{backend}");
+        }
+
+        let check =
+            MixtureOptimization::new(&reading, vec![(r410a.clone(), 0.), (r22.clone(), 0.)])
+                .unwrap()
+                .cross_check_usage(Backend::Microlp, 1e-4)
+                .unwrap();
+
+        assert!(check.agrees(), "{check}");
+
+        // least squares is no answer to the linear program
+        assert!(
+            MixtureOptimization::new(&reading, vec![(r410a, 0.), (r22, 0.)])
+                .unwrap()
+                .with_backend(Backend::Nnls)
+                .optimize_usage()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn usage_beats_scaled_least_squares() {
+        // the least squares fit splits the reading between both blends, scaling it down until
+        // nothing exceeds the reading left 0.375 explained, while all of b alone explains 0.5
+        let a = mixture(
+            "r-407c",
+            &[("r-32", 0.125), ("r-125", 0.375), ("r-134a", 0.5)],
+        );
+        let b = mixture(
+            "r-407f",
+            &[("r-32", 0.5), ("r-125", 0.25), ("r-134a", 0.25)],
+        );
+        let reading = reading(&[("r-32", 0.25), ("r-125", 0.125), ("r-134a", 0.5)]);
+
+        for backend in Backend::ALL.iter().filter(|b| b.is_lp()) {
+            let (concentrations, usage) =
+                MixtureOptimization::new(&reading, vec![(a.clone(), 0.), (b.clone(), 0.)])
+                    .unwrap()
+                    .with_backend(*backend)
+                    .optimize_usage()
+                    .unwrap();
+
+            assert!((usage - 0.5).abs() < 1e-6, "{backend}: {usage}");
+            assert!(concentrations[0].0 < 1e-6, "{backend}");
+            assert!((concentrations[1].0 - 0.5).abs() < 1e-6, "{backend}");
+        }
+    }
+}
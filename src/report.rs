@@ -0,0 +1,437 @@
+use std::{fmt::Write, sync::Arc};
+
+use refrigerants::{
+    ClassificationResult, CompositionBasis, Detection, GCReading, RefrigerantMixture,
+    RefrigerantName,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{ambiguity::AmbiguityGroup, math::Decomposition, solver::Backend};
+
+/// Version of the report layout. Bumped whenever a field is removed or changes meaning, new
+/// optional fields don't bump it.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Columns of [`Report::to_csv`], one row per value in long format.
+pub const CSV_HEADER: &str =
+    "schema_version,config_version,sample,section,method,name,value,detail";
+
+/// Everything the analysis concluded about one reading, in a layout that is kept stable for
+/// machine consumption.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Report {
+    pub schema_version: u32,
+    /// [`Config::version`](crate::config::Config::version) of the config used.
+    pub config_version: String,
+    /// Run or sample the reading belongs to.
+    pub sample: String,
+    pub reading: ReadingReport,
+    pub classifications: Vec<ClassificationReport>,
+    pub optimizations: Vec<OptimizationReport>,
+    /// Candidate mixtures the reading cannot tell apart. Since version 2, before that reports
+    /// didn't look for them and parse with none.
+    #[serde(default)]
+    pub ambiguities: Vec<AmbiguityReport>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReadingReport {
+    pub basis: CompositionBasis,
+    /// Sorted by name, including non-detects that have detection limits.
+    pub components: Vec<ComponentReading>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ComponentReading {
+    pub name: RefrigerantName,
+    pub value: f64,
+    pub detection: Detection,
+    pub lod: Option<f64>,
+    pub loq: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ClassificationReport {
+    pub origin: RefrigerantName,
+    pub label: String,
+    pub purity: f64,
+    pub mixtures: Vec<MixtureShare>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MixtureShare {
+    pub mixture: RefrigerantName,
+    pub concentration: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ComponentResidual {
+    pub name: RefrigerantName,
+    /// Observed minus fitted.
+    pub residual: f64,
+}
+
+/// Breakdown of the reading into mixtures by one optimization method.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OptimizationReport {
+    /// What was optimized, e.g. `usage` or `decomposition`.
+    pub method: String,
+    pub backend: Option<String>,
+    /// Sorted by concentration, largest first.
+    pub concentrations: Vec<MixtureShare>,
+    /// Explained fraction of the reading.
+    pub total: f64,
+    pub residuals: Vec<ComponentResidual>,
+    pub weighted_sse: Option<f64>,
+    pub r_squared: Option<f64>,
+}
+
+/// See [`AmbiguityGroup`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AmbiguityReport {
+    pub mixtures: Vec<RefrigerantName>,
+    pub distinguishing_component: RefrigerantName,
+    pub separation: f64,
+    pub required_sigma: f64,
+}
+
+fn shares<'m>(
+    concentrations: impl Iterator<Item = (f64, &'m RefrigerantName)>,
+) -> Vec<MixtureShare> {
+    let mut shares = concentrations
+        .map(|(concentration, mixture)| MixtureShare {
+            mixture: mixture.clone(),
+            concentration,
+        })
+        .collect::<Vec<_>>();
+
+    shares.sort_by(|a, b| {
+        b.concentration
+            .total_cmp(&a.concentration)
+            .then_with(|| a.mixture.cmp(&b.mixture))
+    });
+
+    shares
+}
+
+impl From<&GCReading> for ReadingReport {
+    fn from(reading: &GCReading) -> Self {
+        let mut names = reading
+            .components()
+            .map(|(name, _)| name)
+            .chain(reading.limited_components().map(|(name, _)| name))
+            .collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+
+        Self {
+            basis: reading.basis(),
+            components: names
+                .into_iter()
+                .map(|name| ComponentReading {
+                    name: name.clone(),
+                    value: reading.get_component(name).copied().unwrap_or(0.),
+                    detection: reading.detection(name),
+                    lod: reading.limits(name).map(|l| l.lod),
+                    loq: reading.limits(name).map(|l| l.loq),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&ClassificationResult> for ClassificationReport {
+    fn from(result: &ClassificationResult) -> Self {
+        Self {
+            origin: result.origin.clone(),
+            label: result.label.clone(),
+            purity: result.purity,
+            mixtures: shares(result.components.iter().map(|(name, &c)| (c, name))),
+        }
+    }
+}
+
+impl From<&AmbiguityGroup> for AmbiguityReport {
+    fn from(group: &AmbiguityGroup) -> Self {
+        Self {
+            mixtures: group
+                .mixtures
+                .iter()
+                .map(|m| m.identifier().clone())
+                .collect(),
+            distinguishing_component: group.distinguishing_component.clone(),
+            separation: group.separation,
+            required_sigma: group.required_sigma,
+        }
+    }
+}
+
+impl Report {
+    pub fn new(sample: &str, config_version: &str, reading: &GCReading) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            config_version: config_version.into(),
+            sample: sample.into(),
+            reading: reading.into(),
+            classifications: vec![],
+            optimizations: vec![],
+            ambiguities: vec![],
+        }
+    }
+
+    pub fn with_classification(mut self, result: &ClassificationResult) -> Self {
+        self.classifications.push(result.into());
+        self
+    }
+
+    /// Adds a result of [`MixtureOptimization::optimize_usage`](crate::math::MixtureOptimization::optimize_usage)
+    /// or one of its variants.
+    pub fn with_optimization(
+        mut self,
+        method: &str,
+        backend: Backend,
+        (concentrations, total): &(Vec<(f64, Arc<RefrigerantMixture>)>, f64),
+    ) -> Self {
+        self.optimizations.push(OptimizationReport {
+            method: method.into(),
+            backend: Some(backend.to_string()),
+            concentrations: shares(concentrations.iter().map(|(c, m)| (*c, m.identifier()))),
+            total: *total,
+            residuals: vec![],
+            weighted_sse: None,
+            r_squared: None,
+        });
+        self
+    }
+
+    pub fn with_decomposition(mut self, method: &str, decomposition: &Decomposition) -> Self {
+        self.optimizations.push(OptimizationReport {
+            method: method.into(),
+            backend: None,
+            concentrations: shares(
+                decomposition
+                    .concentrations
+                    .iter()
+                    .map(|(c, m)| (*c, m.identifier())),
+            ),
+            total: decomposition.concentrations.iter().map(|(c, _)| c).sum(),
+            residuals: decomposition
+                .residuals
+                .iter()
+                .map(|(name, residual)| ComponentResidual {
+                    name: name.clone(),
+                    residual: *residual,
+                })
+                .collect(),
+            weighted_sse: Some(decomposition.weighted_sse),
+            r_squared: Some(decomposition.r_squared),
+        });
+        self
+    }
+
+    pub fn with_ambiguity(mut self, group: &AmbiguityGroup) -> Self {
+        self.ambiguities.push(group.into());
+        self
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+
+    /// Parses a JSON report, rejecting reports written with a newer schema.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let report: Report = serde_json::from_str(json).map_err(|e| e.to_string())?;
+
+        if report.schema_version > SCHEMA_VERSION {
+            return Err(format!(
+                "Report schema version {} is newer than the supported version {}.",
+                report.schema_version, SCHEMA_VERSION
+            ));
+        }
+
+        Ok(report)
+    }
+
+    /// Rows of the report in the [`CSV_HEADER`] layout, without the header so that reports of
+    /// several samples can be concatenated.
+    pub fn to_csv(&self) -> String {
+        let mut rows = String::new();
+
+        let mut row = |section: &str, method: &str, name: &str, value: f64, detail: &str| {
+            writeln!(
+                rows,
+                "{},{},{},{},{},{},{},{}",
+                self.schema_version,
+                csv_field(&self.config_version),
+                csv_field(&self.sample),
+                section,
+                csv_field(method),
+                csv_field(name),
+                value,
+                csv_field(detail)
+            )
+            .expect("writing to a String cannot fail");
+        };
+
+        let basis = self.reading.basis.to_string();
+
+        for c in &self.reading.components {
+            row(
+                "reading",
+                &basis,
+                c.name.as_ref(),
+                c.value,
+                &c.detection.to_string(),
+            );
+        }
+
+        for c in &self.classifications {
+            row("classification", c.origin.as_ref(), &c.label, c.purity, "");
+
+            for share in &c.mixtures {
+                row(
+                    "classification_mixture",
+                    c.origin.as_ref(),
+                    share.mixture.as_ref(),
+                    share.concentration,
+                    &c.label,
+                );
+            }
+        }
+
+        for o in &self.optimizations {
+            let backend = o.backend.as_deref().unwrap_or("");
+
+            row("total", &o.method, "", o.total, backend);
+
+            for share in &o.concentrations {
+                row(
+                    "concentration",
+                    &o.method,
+                    share.mixture.as_ref(),
+                    share.concentration,
+                    backend,
+                );
+            }
+
+            for r in &o.residuals {
+                row("residual", &o.method, r.name.as_ref(), r.residual, backend);
+            }
+
+            if let Some(r_squared) = o.r_squared {
+                row("r_squared", &o.method, "", r_squared, backend);
+            }
+        }
+
+        for a in &self.ambiguities {
+            let mixtures = a
+                .mixtures
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(" ");
+            let component = a.distinguishing_component.as_ref();
+
+            row("ambiguity", &mixtures, component, a.separation, "");
+            row("required_sigma", &mixtures, component, a.required_sigma, "");
+        }
+
+        rows
+    }
+}
+
+/// Quotes a CSV field if it contains a separator, quote or line break.
+pub fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use refrigerants::DetectionLimits;
+
+    use crate::{
+        math::{Loss, MixtureOptimization},
+        test_util::{mixture, name, reading},
+    };
+
+    use super::*;
+
+    #[test]
+    fn json_and_csv() {
+        let r410a = mixture("r-410a", &[("r-32", 0.5), ("r-125", 0.5)]);
+        let reading = reading(&[("r-32", 0.5), ("r-125", 0.49)]).with_limits(HashMap::from([(
+            name("r-22"),
+            DetectionLimits::new(0.0001, 0.0003).unwrap(),
+        )]));
+
+        let decomposition = MixtureOptimization::new(&reading, vec![(r410a.clone(), 0.)])
+            .unwrap()
+            .decompose(
+                Loss::Squared,
+                &HashMap::from([(name("r-32"), 1.), (name("r-125"), 1.)]),
+            )
+            .unwrap();
+
+        let report = Report::new("cylinder, 7", "1+0", &reading)
+            .with_classification(&ClassificationResult {
+                label: "R-410aM".into(),
+                origin: name("r-410a"),
+                purity: 0.99,
+                components: HashMap::from([(name("r-410a"), 0.99)]),
+            })
+            .with_decomposition("decomposition", &decomposition)
+            .with_ambiguity(&AmbiguityGroup {
+                mixtures: vec![
+                    r410a.clone(),
+                    mixture("r-410b", &[("r-32", 0.45), ("r-125", 0.55)]),
+                ],
+                distinguishing_component: name("r-125"),
+                separation: 0.05,
+                required_sigma: 0.025,
+            });
+
+        assert_eq!(report.reading.components.len(), 3);
+        assert_eq!(
+            report.reading.components[1].detection,
+            Detection::NotDetected
+        );
+
+        let parsed = Report::from_json(&report.to_json().unwrap()).unwrap();
+        assert_eq!(parsed, report);
+
+        let csv = report.to_csv();
+        let rows = csv.lines().collect::<Vec<_>>();
+
+        assert!(rows.iter().all(|r| r.starts_with("2,1+0,\"cylinder, 7\",")));
+        assert!(rows.contains(&"2,1+0,\"cylinder, 7\",reading,mass,R-22,0,ND"));
+        assert!(rows.contains(&"2,1+0,\"cylinder, 7\",required_sigma,R-410A R-410B,R-125,0.025,"));
+        assert!(
+            rows.iter()
+                .any(|r| r.contains(",residual,decomposition,R-32,"))
+        );
+
+        let newer = report
+            .to_json()
+            .unwrap()
+            .replace("\"schema_version\": 2", "\"schema_version\": 3");
+        assert!(Report::from_json(&newer).is_err());
+
+        // version 1 reports have no ambiguities
+        let mut old =
+            serde_json::from_str::<serde_json::Value>(&report.to_json().unwrap()).unwrap();
+        old["schema_version"] = 1.into();
+        old.as_object_mut().unwrap().remove("ambiguities");
+        assert!(
+            Report::from_json(&old.to_string())
+                .unwrap()
+                .ambiguities
+                .is_empty()
+        );
+    }
+}
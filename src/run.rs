@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+
+use refrigerants::{
+    AliasTable, ClassificationResult, CompositionBasis, GCReading, MixtureRegistry, RefrigerantName,
+};
+use signal_pipeline::io::Detector;
+
+use crate::{
+    ambiguity::{self, AmbiguityGroup},
+    classify,
+    config::Config,
+    math::{self, MixtureOptimization, OptimizationResult},
+    solver::Backend,
+};
+
+/// What the analysis of one detector concluded, see [`analyze`].
+#[derive(Debug, Clone)]
+pub struct Analysis {
+    /// Calibrated reading, in mole fractions.
+    pub reading: GCReading,
+    /// One per mixture the reading can be compared with, purest first.
+    pub classifications: Vec<ClassificationResult>,
+    /// Result of [`MixtureOptimization::optimize_usage`] over the same mixtures.
+    pub usage: OptimizationResult,
+    /// Groups of candidate mixtures the reading cannot tell apart within
+    /// [`Config::uncertainty`](crate::config::Config::uncertainty), each holding the origin of
+    /// the purest classification.
+    pub ambiguities: Vec<AmbiguityGroup>,
+    pub warnings: Vec<String>,
+}
+
+/// Area percent reading of the peaks a detector integrated, along with warnings about peaks that
+/// could not be assigned to a refrigerant.
+///
+/// Fractions are of the total integrated area, so unidentified and unassigned peaks stay part of
+/// the unexplained remainder. The area of a co-elution in `aliases` is split evenly between its
+/// compounds. Compounds of the method listed with a zero area were not detected and are left out.
+pub fn area_reading(
+    detector: &Detector,
+    aliases: &AliasTable,
+) -> Result<(GCReading, Vec<String>), String> {
+    if detector.peaks.is_empty() {
+        return Err(format!(
+            "Detector {} has no peak table, only runs integrated by the instrument can be analyzed.",
+            detector.name
+        ));
+    }
+
+    let peaks = detector.peaks.iter().filter(|p| p.area > 0.);
+    let total = peaks.clone().map(|p| p.area).sum::<f64>();
+
+    if total <= 0. {
+        return Err(format!(
+            "Detector {} has no integrated peaks.",
+            detector.name
+        ));
+    }
+
+    let mut components: HashMap<RefrigerantName, f64> = HashMap::new();
+    let mut warnings = vec![];
+
+    for peak in peaks {
+        let Some(label) = &peak.label else {
+            continue;
+        };
+
+        match aliases.resolve_all(label) {
+            Ok(names) => {
+                let share = peak.area / total / names.len() as f64;
+
+                for name in names {
+                    *components.entry(name).or_default() += share;
+                }
+            }
+            Err(e) => warnings.push(format!(
+                "Peak at {:.2}s ({:.3}% of the area) left unassigned: {}",
+                peak.top,
+                peak.area / total * 100.,
+                e
+            )),
+        }
+    }
+
+    Ok((
+        GCReading::new(components, CompositionBasis::AreaPercent),
+        warnings,
+    ))
+}
+
+/// Calibrates an area percent reading into mole fractions. Components without a response factor
+/// are given a response factor of 1 and returned, so callers can flag the result.
+pub fn calibrate(
+    reading: &GCReading,
+    response_factors: &HashMap<RefrigerantName, f64>,
+) -> Result<(GCReading, Vec<RefrigerantName>), String> {
+    let mut assumed = reading
+        .components()
+        .map(|(name, _)| name)
+        .chain(reading.limited_components().map(|(name, _)| name))
+        .filter(|name| !response_factors.contains_key(*name))
+        .cloned()
+        .collect::<Vec<_>>();
+    assumed.sort();
+    assumed.dedup();
+
+    let factors = response_factors
+        .iter()
+        .map(|(name, rf)| (name.clone(), *rf))
+        .chain(assumed.iter().map(|name| (name.clone(), 1.)))
+        .collect();
+
+    Ok((reading.calibrate(&factors)?, assumed))
+}
+
+/// Runs peaks → reading → classification on the peaks `detector` integrated, and looks for
+/// candidate mixtures the reading cannot tell apart.
+pub fn analyze(
+    detector: &Detector,
+    config: &Config,
+    registry: &MixtureRegistry,
+    backend: Backend,
+) -> Result<Analysis, String> {
+    let (reading, mut warnings) = area_reading(detector, &config.aliases)?;
+
+    // without any response factors all components are assumed to respond alike, warning
+    // about that on every run would only bury the other warnings
+    let (reading, assumed) = calibrate(&reading, &config.response_factors)?;
+
+    if !assumed.is_empty() && !config.response_factors.is_empty() {
+        warnings.push(format!(
+            "No response factor for {}, assumed 1.",
+            assumed
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
+    let (classifications, failed) =
+        classify::classify_reading(&reading, registry.mixtures(), backend)?;
+    warnings.extend(failed);
+
+    let candidates = registry
+        .mixtures()
+        .iter()
+        .filter(|mix| math::valid_comparison(&reading, mix))
+        .map(|mix| (mix.clone(), 0.))
+        .collect();
+
+    let usage = MixtureOptimization::new(&reading, candidates)
+        .and_then(|problem| problem.with_backend(backend).optimize_usage());
+
+    let ambiguities = ambiguity::find_ambiguities(
+        &reading,
+        registry.mixtures(),
+        &config.uncertainty.measurement(),
+        config.uncertainty.z,
+    )
+    .unwrap_or_else(|e| {
+        warnings.push(format!("Ambiguity detection failed: {}", e));
+        vec![]
+    })
+    .into_iter()
+    // mixtures that all fit the reading poorly look alike too, only a group holding the
+    // origin of the classification can make its label wrong
+    .filter(|group| {
+        classifications.first().is_some_and(|best| {
+            group
+                .mixtures
+                .iter()
+                .any(|m| *m.identifier() == best.origin)
+        })
+    })
+    .collect();
+
+    Ok(Analysis {
+        reading,
+        classifications,
+        usage,
+        ambiguities,
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::DVector;
+    use signal_pipeline::io::{IntegratedPeak, Run};
+
+    use crate::{html::HtmlReport, report::Report};
+
+    use super::*;
+
+    fn peak(label: Option<&str>, area: f64, top: f64) -> IntegratedPeak {
+        IntegratedPeak {
+            label: label.map(String::from),
+            area,
+            height: area,
+            snr: Some(100.),
+            start: top - 0.5,
+            top,
+            end: top + 0.5,
+            baseline: None,
+        }
+    }
+
+    #[test]
+    fn reading_from_peaks() {
+        let detector = Detector {
+            name: "moduleA:tcd".into(),
+            values_per_second: 200.,
+            values: DVector::zeros(0),
+            peaks: vec![
+                peak(Some("R-125"), 40., 57.7),
+                peak(Some("R-32"), 40., 58.4),
+                peak(Some("R-115/R-143"), 10., 58.7),
+                peak(Some("R-22"), 0., 71.5),
+                peak(None, 5., 75.),
+                peak(Some("R-1234YF"), 5., 62.4),
+            ],
+        };
+        let name = |n: &str| n.parse::<RefrigerantName>().unwrap();
+
+        let (reading, warnings) = area_reading(&detector, &AliasTable::new()).unwrap();
+
+        assert_eq!(reading.get_component(&name("R-32")), Some(&0.4));
+        assert_eq!(reading.get_component(&name("R-1234yf")), Some(&0.05));
+        assert_eq!(reading.get_component(&name("R-22")), None);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("R-115/R-143"));
+
+        let mut aliases = AliasTable::new();
+        aliases.insert_coelution("R-115/R-143", vec![name("R-115"), name("R-143")]);
+        let (coeluted, warnings) = area_reading(&detector, &aliases).unwrap();
+
+        assert!(warnings.is_empty());
+        assert_eq!(coeluted.get_component(&name("R-115")), Some(&0.05));
+        assert_eq!(coeluted.get_component(&name("R-143")), Some(&0.05));
+
+        let (calibrated, assumed) = calibrate(
+            &reading,
+            &HashMap::from([(name("R-32"), 2.), (name("R-125"), 1.)]),
+        )
+        .unwrap();
+
+        assert_eq!(calibrated.basis(), CompositionBasis::Mole);
+        assert_eq!(assumed, vec![name("R-1234yf")]);
+        assert!(
+            calibrated.get_component(&name("R-32")).unwrap()
+                > calibrated.get_component(&name("R-125")).unwrap()
+        );
+    }
+
+    #[test]
+    fn r401a_and_r401b_are_ambiguous() {
+        let detector = Detector {
+            name: "moduleA:tcd".into(),
+            values_per_second: 10.,
+            values: DVector::from_fn(1000, |i, _| (i % 100) as f64),
+            peaks: vec![
+                peak(Some("R-22"), 55., 20.),
+                peak(Some("R-152a"), 12.5, 40.),
+                peak(Some("R-124"), 32.5, 60.),
+            ],
+        };
+        let json = std::fs::read_to_string("config.json").unwrap();
+        let coarse =
+            Config::from_json(&json.replacen('{', r#"{ "uncertainty": { "relative": 0.15 },"#, 1))
+                .unwrap();
+        let registry = coarse.registry().unwrap();
+
+        let analysis = analyze(&detector, &coarse, &registry, Backend::default()).unwrap();
+        let group = analysis
+            .ambiguities
+            .iter()
+            .find(|g| {
+                g.mixtures
+                    .iter()
+                    .any(|m| m.identifier().as_ref() == "R-401A")
+            })
+            .unwrap();
+        assert!(
+            group
+                .mixtures
+                .iter()
+                .any(|m| m.identifier().as_ref() == "R-401B")
+        );
+        assert_eq!(group.distinguishing_component.as_ref(), "R-22");
+
+        let report =
+            Report::new("r401", &coarse.version(), &analysis.reading).with_ambiguity(group);
+        let parsed = Report::from_json(&report.to_json().unwrap()).unwrap();
+        assert_eq!(parsed.ambiguities[0].mixtures.len(), group.mixtures.len());
+
+        let run = Run {
+            id: "r401".into(),
+            name: "Cylinder 401".into(),
+            timestamp: "2025-06-08T09:24:00Z".into(),
+            method: "Golden".into(),
+            serial_number: "70152956".into(),
+            tags: vec![],
+            detectors: vec![detector.clone()],
+        };
+        let html = HtmlReport::new(&run, &coarse, &registry, &analysis)
+            .unwrap()
+            .render()
+            .unwrap();
+        assert!(html.contains("<h2>Ambiguities</h2>"));
+        assert!(html.contains("R-401B"));
+
+        // the default precision tells them apart
+        let config = Config::from_json(&json).unwrap();
+        let analysis = analyze(&detector, &config, &registry, Backend::default()).unwrap();
+        assert!(analysis.ambiguities.iter().all(|g| {
+            g.mixtures
+                .iter()
+                .all(|m| m.identifier().as_ref() != "R-401A")
+        }));
+    }
+}
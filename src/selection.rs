@@ -0,0 +1,187 @@
+use std::{collections::HashMap, fmt::Display, sync::Arc};
+
+use itertools::Itertools;
+use refrigerants::{GCReading, RefrigerantMixture, RefrigerantName};
+
+use crate::math::{Decomposition, Loss, MixtureOptimization};
+
+/// Concentrations below this are treated as the mixture not being part of the explanation.
+const MIN_CONCENTRATION: f64 = 1e-6;
+
+/// How a fit's weighted residual is traded against the number of mixtures it uses. Lower
+/// scores are better; with inverse variance weights the residual is a chi-square.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InformationCriterion {
+    /// `chi2 + 2k`
+    Aic,
+    /// `chi2 + k ln(n)` for `n` components, stricter than AIC on larger readings.
+    Bic,
+    /// `chi2 + penalty * k`
+    Sparsity(f64),
+}
+
+/// One candidate set of mixtures explaining a reading.
+#[derive(Debug, Clone)]
+pub struct Explanation {
+    pub decomposition: Decomposition,
+    pub score: f64,
+    /// Akaike style weight of this explanation among all ranked ones, summing to 1.
+    pub weight: f64,
+}
+
+impl InformationCriterion {
+    fn score(&self, chi_square: f64, mixtures: usize, components: usize) -> f64 {
+        let k = mixtures as f64;
+
+        chi_square
+            + match self {
+                InformationCriterion::Aic => 2. * k,
+                InformationCriterion::Bic => k * (components as f64).ln(),
+                InformationCriterion::Sparsity(penalty) => penalty * k,
+            }
+    }
+}
+
+/// Fits every subset of up to `max_mixtures` candidates to `reading` and ranks the resulting
+/// explanations by `criterion`, best first.
+///
+/// Only candidates sharing a component with the reading are considered, and subsets in which
+/// a mixture ends up unused are dropped since a smaller subset explains the reading equally.
+pub fn rank_explanations(
+    reading: &GCReading,
+    candidates: &[Arc<RefrigerantMixture>],
+    weights: &HashMap<RefrigerantName, f64>,
+    criterion: InformationCriterion,
+    max_mixtures: usize,
+) -> Result<Vec<Explanation>, String> {
+    let observed = reading.component_set();
+
+    let candidates = candidates
+        .iter()
+        .filter(|m| !m.component_set().is_disjoint(&observed))
+        .collect::<Vec<_>>();
+
+    let mut explanations = vec![];
+
+    for size in 1..=max_mixtures.min(candidates.len()) {
+        for subset in candidates.iter().combinations(size) {
+            let decomposition = MixtureOptimization::new(
+                reading,
+                subset.into_iter().map(|m| (Arc::clone(m), 0.)).collect(),
+            )?
+            .decompose(Loss::Squared, weights)?;
+
+            if decomposition
+                .concentrations
+                .iter()
+                .any(|(c, _)| *c < MIN_CONCENTRATION)
+            {
+                continue;
+            }
+
+            let score = criterion.score(
+                decomposition.weighted_sse,
+                size,
+                decomposition.residuals.len(),
+            );
+
+            explanations.push(Explanation {
+                decomposition,
+                score,
+                weight: 0.,
+            });
+        }
+    }
+
+    explanations.sort_by(|a, b| a.score.total_cmp(&b.score));
+
+    if let Some(best) = explanations.first().map(|e| e.score) {
+        let likelihoods = explanations
+            .iter()
+            .map(|e| (-(e.score - best) / 2.).exp())
+            .collect::<Vec<_>>();
+        let total = likelihoods.iter().sum::<f64>();
+
+        explanations
+            .iter_mut()
+            .zip(likelihoods)
+            .for_each(|(e, l)| e.weight = l / total);
+    }
+
+    Ok(explanations)
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} (score {:.3}, weight {:.3}, R² {:.5})",
+            self.decomposition
+                .concentrations
+                .iter()
+                .sorted_by(|a, b| b.0.total_cmp(&a.0))
+                .map(|(c, mix)| format!("{} {:.3}%", mix.identifier(), c * 100.))
+                .join(" + "),
+            self.score,
+            self.weight,
+            self.decomposition.r_squared
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::{mixture, name, reading};
+
+    use super::*;
+
+    #[test]
+    fn prefers_blend_over_its_components() {
+        let mixtures = [
+            mixture(
+                "r-404a",
+                &[("r-125", 0.44), ("r-143a", 0.52), ("r-134a", 0.04)],
+            ),
+            mixture("r-507a", &[("r-125", 0.5), ("r-143a", 0.5)]),
+            mixture("r-125", &[("r-125", 1.)]),
+            mixture("r-143a", &[("r-143a", 1.)]),
+            mixture("r-134a", &[("r-134a", 1.)]),
+            mixture("r-22", &[("r-22", 1.)]),
+            mixture("r-32", &[("r-32", 1.)]),
+        ];
+
+        let reading = reading(&[
+            ("r-125", 0.97 * 0.44),
+            ("r-143a", 0.97 * 0.52),
+            ("r-134a", 0.97 * 0.04),
+            ("r-22", 0.03),
+        ]);
+
+        // 0.1% absolute measurement noise
+        let weights = reading
+            .components()
+            .map(|(n, _)| (n.clone(), 1e6))
+            .collect::<HashMap<_, _>>();
+
+        let ranked =
+            rank_explanations(&reading, &mixtures, &weights, InformationCriterion::Bic, 3).unwrap();
+
+        let best = &ranked[0];
+        let ids = best
+            .decomposition
+            .concentrations
+            .iter()
+            .map(|(_, m)| m.identifier().to_string())
+            .sorted()
+            .collect::<Vec<_>>();
+
+        assert_eq!(ids, vec!["R-22", "R-404A"], "{}", best);
+        assert!(best.weight > 0.9);
+        assert!(ranked.iter().all(|e| {
+            e.decomposition
+                .concentrations
+                .iter()
+                .all(|(_, m)| m.identifier() != &name("r-32"))
+        }));
+    }
+}
@@ -0,0 +1,224 @@
+use std::{fmt::Display, str::FromStr};
+
+use good_lp::{
+    Constraint, Expression, ProblemVariables, Solution, Solver, SolverModel, Variable, variable,
+    variables,
+};
+use nalgebra::{DMatrix, DVector};
+
+/// Solver for the linear programs of [`MixtureOptimization`](crate::math::MixtureOptimization).
+///
+/// Clarabel and HiGHS are compiled in with the cargo feature of the same name, the pure Rust
+/// [`Backend::Microlp`] and [`Backend::Nnls`] are always available.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Backend {
+    #[cfg(feature = "clarabel")]
+    Clarabel,
+    #[cfg(feature = "highs")]
+    Highs,
+    Microlp,
+    /// Pure Rust non-negative least squares, only for
+    /// [`MixtureOptimization::decompose`](crate::math::MixtureOptimization::decompose) with
+    /// squared loss. It cannot solve the linear programs.
+    Nnls,
+}
+
+impl Backend {
+    /// The compiled in backends, in order of preference.
+    pub const ALL: &[Backend] = &[
+        #[cfg(feature = "clarabel")]
+        Backend::Clarabel,
+        #[cfg(feature = "highs")]
+        Backend::Highs,
+        Backend::Microlp,
+        Backend::Nnls,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "clarabel")]
+            Backend::Clarabel => "clarabel",
+            #[cfg(feature = "highs")]
+            Backend::Highs => "highs",
+            Backend::Microlp => "microlp",
+            Backend::Nnls => "nnls",
+        }
+    }
+
+    /// Whether the backend solves linear programs exactly.
+    pub fn is_lp(&self) -> bool {
+        *self != Backend::Nnls
+    }
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        Backend::ALL[0]
+    }
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase();
+
+        Backend::ALL
+            .iter()
+            .find(|b| b.name() == name || (name == "minilp" && b.name() == "microlp"))
+            .copied()
+            .ok_or(format!(
+                "Unknown or not compiled in solver backend {}, available: {}.",
+                s,
+                Backend::ALL
+                    .iter()
+                    .map(Backend::name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+    }
+}
+
+impl Display for Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+fn solve_with<S: Solver>(
+    solver: S,
+    problem_variables: ProblemVariables,
+    objective: Expression,
+    constraints: Vec<Constraint>,
+    x: &[Variable],
+) -> Result<Vec<f64>, String> {
+    let solution = problem_variables
+        .maximise(objective)
+        .using(solver)
+        .with_all(constraints)
+        .solve()
+        .map_err(|e| e.to_string())?;
+
+    Ok(x.iter().map(|v| solution.value(*v)).collect())
+}
+
+/// `max c x` subject to `a x <= b` and `lower <= x <= upper`.
+///
+/// Solutions are clamped into the variable bounds, interior point solvers overshoot them by
+/// their tolerance, which showed up as slightly negative or over 100% concentrations.
+pub(crate) fn solve_lp(
+    backend: Backend,
+    c: &DVector<f64>,
+    a: &DMatrix<f64>,
+    b: &DVector<f64>,
+    lower: &DVector<f64>,
+    upper: &DVector<f64>,
+) -> Result<DVector<f64>, String> {
+    match backend {
+        Backend::Nnls => Err(format!(
+            "The {} backend cannot solve linear programs.",
+            backend
+        )),
+        lp => linear_program(lp, c, a, b, lower, upper),
+    }
+}
+
+fn linear_program(
+    backend: Backend,
+    c: &DVector<f64>,
+    a: &DMatrix<f64>,
+    b: &DVector<f64>,
+    lower: &DVector<f64>,
+    upper: &DVector<f64>,
+) -> Result<DVector<f64>, String> {
+    let mut problem_variables = variables! {};
+
+    let x: Vec<Variable> = lower
+        .iter()
+        .zip(upper.iter())
+        .map(|(&l, &u)| problem_variables.add(variable().min(l).max(u)))
+        .collect();
+
+    let linear = |coefficients: &mut dyn Iterator<Item = &f64>| {
+        coefficients
+            .zip(x.iter())
+            .map(|(&a, &x)| a * x)
+            .sum::<Expression>()
+    };
+
+    let objective = linear(&mut c.iter());
+    let constraints = a
+        .row_iter()
+        .zip(b.iter())
+        .map(|(row, &b)| linear(&mut row.iter()).leq(b))
+        .collect::<Vec<_>>();
+
+    let values = match backend {
+        #[cfg(feature = "clarabel")]
+        Backend::Clarabel => solve_with(
+            good_lp::solvers::clarabel::clarabel,
+            problem_variables,
+            objective,
+            constraints,
+            &x,
+        ),
+        #[cfg(feature = "highs")]
+        Backend::Highs => solve_with(
+            good_lp::solvers::highs::highs,
+            problem_variables,
+            objective,
+            constraints,
+            &x,
+        ),
+        Backend::Microlp => solve_with(
+            good_lp::solvers::microlp::microlp,
+            problem_variables,
+            objective,
+            constraints,
+            &x,
+        ),
+        Backend::Nnls => unreachable!("checked by solve_lp"),
+    }?;
+
+    Ok(DVector::from_iterator(
+        values.len(),
+        values
+            .into_iter()
+            .zip(lower.iter().zip(upper.iter()))
+            .map(|(v, (&l, &u))| v.clamp(l, u)),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_backends() {
+        for backend in Backend::ALL {
+            assert_eq!(backend.name().parse::<Backend>(), Ok(*backend));
+        }
+
+        assert_eq!(" NNLS ".parse::<Backend>(), Ok(Backend::Nnls));
+        assert!("cplex".parse::<Backend>().is_err());
+    }
+
+    #[test]
+    fn lp_solutions_stay_in_bounds() {
+        // max x + y subject to x + 2y <= 1, 0 <= x, y <= 1
+        let c = DVector::from_column_slice(&[1., 1.]);
+        let a = DMatrix::from_row_slice(1, 2, &[1., 2.]);
+        let b = DVector::from_column_slice(&[1.]);
+        let lower = DVector::zeros(2);
+        let upper = DVector::from_element(2, 1.);
+
+        for backend in Backend::ALL.iter().filter(|b| b.is_lp()) {
+            let x = solve_lp(*backend, &c, &a, &b, &lower, &upper).unwrap();
+
+            assert!((x[0] - 1.).abs() < 1e-6, "{backend}: {x}");
+            assert!(x.iter().all(|v| (0. ..=1.).contains(v)), "{backend}: {x}");
+        }
+
+        assert!(solve_lp(Backend::Nnls, &c, &a, &b, &lower, &upper).is_err());
+    }
+}
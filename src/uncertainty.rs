@@ -0,0 +1,279 @@
+use std::{collections::HashMap, sync::Arc};
+
+use rand::Rng;
+use refrigerants::{Detection, GCReading, RefrigerantMixture, RefrigerantName};
+use statrs::{
+    distribution::{ContinuousCDF, Normal},
+    statistics::{Data, OrderStatistics, Statistics},
+};
+
+use crate::math::{Decomposition, Loss, MixtureOptimization};
+
+/// Smallest standard deviation used for weighting, so exactly quantified components don't get
+/// an infinite weight.
+const MIN_SIGMA: f64 = 1e-6;
+
+/// Per-component measurement standard deviations, in the units of the reading.
+#[derive(Debug, Clone, Default)]
+pub struct MeasurementUncertainty {
+    sigma: HashMap<RefrigerantName, f64>,
+    /// Relative standard deviation for components without an explicit sigma.
+    default_relative: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfidenceInterval {
+    /// Point estimate from the unperturbed reading.
+    pub estimate: f64,
+    pub std_dev: f64,
+    pub lower: f64,
+    pub upper: f64,
+    samples: Vec<f64>,
+}
+
+/// A decomposition of the reading with Monte Carlo confidence intervals per mixture.
+#[derive(Debug, Clone)]
+pub struct UncertainDecomposition {
+    pub decomposition: Decomposition,
+    pub intervals: Vec<(ConfidenceInterval, Arc<RefrigerantMixture>)>,
+}
+
+impl MeasurementUncertainty {
+    pub fn new(sigma: HashMap<RefrigerantName, f64>, default_relative: f64) -> Self {
+        Self {
+            sigma,
+            default_relative,
+        }
+    }
+
+    /// Derives sigmas from peak signal-to-noise ratios, `sigma = value / snr`.
+    pub fn from_snr(
+        reading: &GCReading,
+        snr: &HashMap<RefrigerantName, f64>,
+        default_relative: f64,
+    ) -> Self {
+        Self::new(
+            reading
+                .components()
+                .filter_map(|(name, &v)| {
+                    snr.get(name)
+                        .filter(|&&snr| snr > 0.)
+                        .map(|snr| (name.clone(), v / snr))
+                })
+                .collect(),
+            default_relative,
+        )
+    }
+
+    pub fn sigma(&self, name: &RefrigerantName, value: f64) -> f64 {
+        self.sigma
+            .get(name)
+            .copied()
+            .unwrap_or(self.default_relative * value.abs())
+    }
+
+    /// Sigma of a component of `reading`, widened to what its detection limits allow: a
+    /// non-detect is anywhere between 0 and the LOD (RMS `lod / sqrt(3)` from the reported 0),
+    /// a value below the LOQ anywhere in a band of that width (`loq / sqrt(12)`).
+    pub fn reading_sigma(&self, reading: &GCReading, name: &RefrigerantName) -> f64 {
+        let value = reading.get_component(name).copied().unwrap_or(0.);
+        let sigma = self.sigma(name, value);
+
+        let limit = match (reading.detection(name), reading.limits(name)) {
+            (Detection::NotDetected, Some(limits)) => limits.lod / 3f64.sqrt(),
+            (Detection::BelowQuantitation, Some(limits)) => limits.loq / 12f64.sqrt(),
+            _ => 0.,
+        };
+
+        sigma.max(limit).max(MIN_SIGMA)
+    }
+
+    /// Inverse variance weights for [`MixtureOptimization::decompose`] of `reading` into
+    /// `mixtures`, covering the reported components, non-detects with known limits and every
+    /// component of the mixtures. A component the reading lacks is weighted as measured at zero,
+    /// so a mixture predicting it is penalized like for any other misfit.
+    pub fn weights<'m>(
+        &self,
+        reading: &GCReading,
+        mixtures: impl IntoIterator<Item = &'m RefrigerantMixture>,
+    ) -> HashMap<RefrigerantName, f64> {
+        reading
+            .components()
+            .map(|(name, _)| name.clone())
+            .chain(reading.limited_components().map(|(name, _)| name.clone()))
+            .chain(
+                mixtures
+                    .into_iter()
+                    .flat_map(|m| m.components().map(|(name, _)| name.clone())),
+            )
+            .map(|name| {
+                let weight = self.reading_sigma(reading, &name).powi(-2);
+                (name, weight)
+            })
+            .collect()
+    }
+}
+
+impl ConfidenceInterval {
+    /// Fraction of the Monte Carlo samples above `limit`, e.g. how likely a contamination
+    /// really exceeds a specification limit.
+    pub fn probability_above(&self, limit: f64) -> f64 {
+        self.samples.iter().filter(|&&s| s > limit).count() as f64 / self.samples.len() as f64
+    }
+}
+
+/// Propagates measurement uncertainty into the decomposition of `reading` by refitting
+/// `samples` readings perturbed with independent gaussian noise (clamped at zero). Components
+/// that were not detected are drawn uniformly between zero and their limit of detection.
+///
+/// `confidence` is the two-sided coverage of the reported intervals, e.g. 0.95.
+pub fn monte_carlo(
+    reading: &GCReading,
+    mixtures: &[(Arc<RefrigerantMixture>, f64)],
+    loss: Loss,
+    uncertainty: &MeasurementUncertainty,
+    samples: usize,
+    confidence: f64,
+    rng: &mut impl Rng,
+) -> Result<UncertainDecomposition, String> {
+    if samples < 2 || confidence <= 0. || confidence >= 1. {
+        return Err("Need at least two samples and a confidence between 0 and 1.".into());
+    }
+
+    let weights = uncertainty.weights(reading, mixtures.iter().map(|(m, _)| m.as_ref()));
+    let standard_normal = Normal::standard();
+
+    let decomposition =
+        MixtureOptimization::new(reading, mixtures.to_vec())?.decompose(loss, &weights)?;
+
+    let mut draws = vec![Vec::with_capacity(samples); mixtures.len()];
+
+    for _ in 0..samples {
+        let mut components = reading
+            .components()
+            .map(|(name, &v)| {
+                let noise = standard_normal.inverse_cdf(rng.random_range(f64::EPSILON..1.));
+                (
+                    name.clone(),
+                    (v + noise * uncertainty.sigma(name, v)).max(0.),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+
+        for (name, limits) in reading.limited_components() {
+            if reading.detection(name) == Detection::NotDetected && limits.lod > 0. {
+                components.insert(name.clone(), rng.random_range(0. ..limits.lod));
+            }
+        }
+
+        let perturbed = GCReading::new(components, reading.basis());
+
+        let fit =
+            MixtureOptimization::new(&perturbed, mixtures.to_vec())?.decompose(loss, &weights)?;
+
+        draws
+            .iter_mut()
+            .zip(fit.concentrations)
+            .for_each(|(draw, (c, _))| draw.push(c));
+    }
+
+    let tail = (1. - confidence) / 2.;
+
+    let intervals = draws
+        .into_iter()
+        .zip(decomposition.concentrations.iter())
+        .map(|(samples, (estimate, mix))| {
+            let mut data = Data::new(samples.clone());
+
+            (
+                ConfidenceInterval {
+                    estimate: *estimate,
+                    std_dev: samples.iter().std_dev(),
+                    lower: data.quantile(tail),
+                    upper: data.quantile(1. - tail),
+                    samples,
+                },
+                mix.clone(),
+            )
+        })
+        .collect();
+
+    Ok(UncertainDecomposition {
+        decomposition,
+        intervals,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use crate::test_util::{mixture, name, reading};
+
+    use super::*;
+
+    #[test]
+    fn contamination_near_limit() {
+        let r410a = mixture("r-410a", &[("r-32", 0.5), ("r-125", 0.5)]);
+        let r22 = mixture("r-22", &[("r-22", 1.)]);
+
+        let reading = reading(&[("r-32", 0.4895), ("r-125", 0.4895), ("r-22", 0.021)]);
+
+        let uncertainty = MeasurementUncertainty::new(HashMap::new(), 0.05);
+
+        let result = monte_carlo(
+            &reading,
+            &[(r410a, 0.), (r22, 0.)],
+            Loss::Squared,
+            &uncertainty,
+            500,
+            0.95,
+            &mut StdRng::seed_from_u64(7),
+        )
+        .unwrap();
+
+        let (r22_interval, _) = &result.intervals[1];
+
+        assert!((r22_interval.estimate - 0.021).abs() < 1e-6);
+        assert!(r22_interval.lower < 0.02 && r22_interval.upper > 0.021);
+
+        // 2.1% with 5% relative noise is only somewhat likely to be above a 2% limit
+        let p = r22_interval.probability_above(0.02);
+        assert!(p > 0.6 && p < 0.95, "{p}");
+    }
+
+    #[test]
+    fn absent_component_counts_against_blend() {
+        let r410a = mixture("r-410a", &[("r-32", 0.5), ("r-125", 0.5)]);
+        let blend = mixture(
+            "r-463a",
+            &[("r-32", 0.45), ("r-125", 0.45), ("r-1234yf", 0.1)],
+        );
+
+        let reading = reading(&[("r-32", 0.5), ("r-125", 0.5)]);
+        let uncertainty = MeasurementUncertainty::new(HashMap::new(), 0.01);
+        let weights = uncertainty.weights(&reading, [r410a.as_ref(), blend.as_ref()]);
+
+        assert_eq!(weights[&name("r-1234yf")], MIN_SIGMA.powi(-2));
+
+        let fit = |mixture: &Arc<RefrigerantMixture>| {
+            MixtureOptimization::new(&reading, vec![(mixture.clone(), 0.)])
+                .unwrap()
+                .decompose(Loss::Squared, &weights)
+                .unwrap()
+                .weighted_sse
+        };
+
+        // the blend's 10% R-1234yf was measured as absent, far outside any noise
+        assert!(fit(&r410a) < 1e-9);
+        assert!(fit(&blend) > 1e3, "{}", fit(&blend));
+
+        // without a weight for it the component can't be fitted at all
+        assert!(
+            MixtureOptimization::new(&reading, vec![(blend.clone(), 0.)])
+                .unwrap()
+                .decompose(Loss::Squared, &uncertainty.weights(&reading, []))
+                .is_err()
+        );
+    }
+}